@@ -1,12 +1,35 @@
-use lib_agents::{HumanAgent, MctsAgent};
-use lib_boardgame::{GameRunner, GeneralGameRunner, PlayerColor};
+use lib_agents::{HumanAgent, MctsAgent, MctsConfig, MctsEndCondition, RaveConfig};
+use lib_boardgame::{GameAgent, GameResult, GameRunner, GameState, GeneralGameRunner, PlayerColor};
 use lib_connect_four::ConnectFourState;
-use lib_reversi::ReversiState;
+use lib_reversi::{PassRule, ReversiState, ReversiVariant, SizedReversiState};
 use lib_tic_tac_toe::tic_tac_toe_gamestate::TicTacToeState;
 
+/// The rollouts each of an agent's search threads performs per move in a RAVE match,
+/// unless given on the command line.
+const DEFAULT_MATCH_ROLLOUTS: usize = 2_000;
+
 fn main() {
-    play_reversi();
-    // play_connect_four();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        // rave-match <games> [rollouts]
+        Some("rave-match") => {
+            let games = args.get(1).and_then(|s| s.parse().ok());
+            let rollouts = match args.get(2) {
+                Some(s) => s.parse().ok(),
+                None => Some(DEFAULT_MATCH_ROLLOUTS),
+            };
+
+            match (games, rollouts) {
+                (Some(games), Some(rollouts)) => play_rave_match(games, rollouts),
+                _ => eprintln!("Usage: game_runner rave-match <games> [rollouts per move]"),
+            }
+        }
+        _ => {
+            play_reversi();
+            // play_connect_four();
+        }
+    }
 }
 
 #[allow(unused)]
@@ -15,15 +38,39 @@ fn play_reversi() -> lib_boardgame::GameResult {
     // let white = MctsAgent::<ReversiState>::new(PlayerColor::White);
     let white = HumanAgent::new(PlayerColor::White);
 
+    GeneralGameRunner::play_to_end_with_take_backs(&black, &white)
+}
+
+/// Plays MCTS against itself on a 6x6 board, which is small enough to solve exactly.
+#[allow(unused)]
+fn play_small_reversi() -> lib_boardgame::GameResult {
+    let black = MctsAgent::<SizedReversiState<6>>::new(PlayerColor::Black);
+    let white = MctsAgent::<SizedReversiState<6>>::new(PlayerColor::White);
+
     GeneralGameRunner::play_to_end(&black, &white)
 }
 
+/// Plays MCTS against itself at anti-Reversi (where the fewest discs win),
+/// with turns passed automatically.
+#[allow(unused)]
+fn play_anti_reversi() -> lib_boardgame::GameResult {
+    let variant = ReversiVariant {
+        passing: PassRule::AutoPass,
+        ..ReversiVariant::anti()
+    };
+
+    let black = MctsAgent::<ReversiState>::new(PlayerColor::Black);
+    let white = MctsAgent::<ReversiState>::new(PlayerColor::White);
+
+    GeneralGameRunner::play_from(ReversiState::with_variant(variant), &black, &white)
+}
+
 #[allow(unused)]
 fn play_tic_tac_toe() -> lib_boardgame::GameResult {
     let black = HumanAgent::new(PlayerColor::Black);
     let white = MctsAgent::<TicTacToeState>::new(PlayerColor::White);
 
-    GeneralGameRunner::play_to_end(&black, &white)
+    GeneralGameRunner::play_to_end_with_take_backs(&black, &white)
 }
 
 #[allow(unused)]
@@ -33,3 +80,73 @@ fn play_connect_four() -> lib_boardgame::GameResult {
 
     GeneralGameRunner::play_to_end(&black, &white)
 }
+
+/// Plays a series of Connect Four games between an MCTS agent using RAVE
+/// and a plain MCTS agent, alternating colors each game,
+/// and prints the tally of results from the RAVE agent's perspective.
+/// Both agents get the same number of rollouts per move, so the match compares
+/// the quality of their search rather than their speed.
+fn play_rave_match(games: usize, rollouts: usize) {
+    let plain_config = MctsConfig {
+        end_condition: MctsEndCondition::RolloutCount(rollouts),
+        ..Default::default()
+    };
+
+    let rave_config = MctsConfig {
+        rave: Some(RaveConfig::default()),
+        ..plain_config
+    };
+
+    let (mut rave_wins, mut plain_wins, mut ties) = (0, 0, 0);
+
+    for game in 0..games {
+        let rave_color = if game % 2 == 0 {
+            PlayerColor::Black
+        } else {
+            PlayerColor::White
+        };
+
+        let rave = MctsAgent::<ConnectFourState>::with_config(rave_color, rave_config);
+        let plain = MctsAgent::<ConnectFourState>::with_config(rave_color.opponent(), plain_config);
+
+        let result = match rave_color {
+            PlayerColor::Black => GeneralGameRunner::play_to_end(&rave, &plain),
+            PlayerColor::White => GeneralGameRunner::play_to_end(&plain, &rave),
+        };
+
+        match result {
+            GameResult::Tie => ties += 1,
+            r if r.is_win_for_player(rave_color) => rave_wins += 1,
+            _ => plain_wins += 1,
+        }
+
+        println!(
+            "After {} games: RAVE {} / plain {} / tie {}",
+            game + 1,
+            rave_wins,
+            plain_wins,
+            ties
+        );
+    }
+}
+
+/// Searches the initial Reversi position as Black for the given number of rollouts,
+/// and saves the resulting tree, so it can be shipped as an opening tree
+/// (see REVERSI_OPENING_TREE_BLACK in the NBoard runner).
+#[allow(unused)]
+fn precompute_opening_tree(path: &str, rollouts: usize) {
+    let config = MctsConfig {
+        end_condition: MctsEndCondition::RolloutCount(rollouts),
+        ..Default::default()
+    };
+
+    let black = MctsAgent::<ReversiState>::with_config(PlayerColor::Black, config);
+    let state = ReversiState::initial_state();
+
+    black.pick_move(&state, state.legal_moves());
+
+    match black.save_tree(path) {
+        Ok(()) => println!("Saved opening tree to {}", path),
+        Err(e) => println!("Couldn't save opening tree: {}", e),
+    }
+}
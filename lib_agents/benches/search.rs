@@ -0,0 +1,110 @@
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use lib_agents::{tree_search_bench as tree_search, MctsConfig, MctsEndCondition};
+use lib_boardgame::{GameState, PlayerColor};
+use lib_connect_four::ConnectFourState;
+use lib_reversi::ReversiState;
+use lib_tic_tac_toe::tic_tac_toe_gamestate::TicTacToeState;
+use monte_carlo_tree::{arc_tree::ArcNode, monte_carlo_data::MctsData, tree::Node};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+type ReversiNode = ArcNode<MctsData<ReversiState>>;
+
+/// The total rollouts in each full search, shared among its threads.
+const SEARCH_ROLLOUTS: usize = 4_000;
+
+fn new_root() -> ReversiNode {
+    ReversiNode::new_root(MctsData::new(ReversiState::initial_state(), 0, 0, None))
+}
+
+/// Plays uniformly random moves until the game is over, as an MCTS simulation does.
+fn playout<T: GameState>(rng: &mut StdRng) -> T {
+    let mut state = T::initial_state();
+
+    while !state.is_game_over() {
+        let action = *state.legal_moves().choose(rng).unwrap();
+        state.apply_move(action);
+    }
+
+    state
+}
+
+fn bench_playouts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random playout");
+    let mut rng = StdRng::seed_from_u64(0);
+
+    group.bench_function("reversi", |b| b.iter(|| playout::<ReversiState>(&mut rng)));
+    group.bench_function("connect four", |b| {
+        b.iter(|| playout::<ConnectFourState>(&mut rng))
+    });
+    group.bench_function("tic-tac-toe", |b| {
+        b.iter(|| playout::<TicTacToeState>(&mut rng))
+    });
+
+    group.finish();
+}
+
+fn bench_expand(c: &mut Criterion) {
+    c.bench_function("tree_search expand (reversi root)", |b| {
+        b.iter_batched(
+            new_root,
+            |root| {
+                tree_search::expand(&root);
+                root
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_select_to_leaf(c: &mut Criterion) {
+    let config = MctsConfig::deterministic(0, SEARCH_ROLLOUTS);
+    let root = new_root();
+    tree_search::mcts::<ReversiNode, _>(root.clone(), PlayerColor::Black, config);
+
+    c.bench_function(
+        "tree_search select_to_leaf (reversi, 4000-rollout tree)",
+        |b| b.iter(|| tree_search::select_to_leaf(black_box(&root), PlayerColor::Black, config)),
+    );
+}
+
+fn bench_mcts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mcts reversi 4000 rollouts");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(SEARCH_ROLLOUTS as u64));
+
+    for &threads in &[1, 2, 4, 8] {
+        // The rollout count applies to each thread, so the total is the same for every run.
+        let config = MctsConfig {
+            end_condition: MctsEndCondition::RolloutCount(SEARCH_ROLLOUTS / threads),
+            thread_count: Some(threads),
+            ..Default::default()
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &config,
+            |b, &config| {
+                b.iter_batched(
+                    new_root,
+                    |root| tree_search::mcts::<ReversiNode, _>(root, PlayerColor::Black, config),
+                    BatchSize::PerIteration,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_playouts,
+    bench_expand,
+    bench_select_to_leaf,
+    bench_mcts
+);
+criterion_main!(benches);
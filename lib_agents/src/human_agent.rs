@@ -1,4 +1,4 @@
-use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use lib_boardgame::{GameAction, GameAgent, GameState, PlayerColor, TurnChoice};
 use std::marker::PhantomData;
 use std::str::FromStr;
 
@@ -7,6 +7,10 @@ where
     TState::Action: FromStr,
 {
     player_color: PlayerColor,
+
+    /// Describes the expected move input in prompts, e.g. "a1-h8 or pass".
+    move_hint: String,
+
     _phantom: PhantomData<TState>,
 }
 
@@ -15,17 +19,34 @@ where
     TState::Action: FromStr,
     <TState::Action as FromStr>::Err: std::fmt::Debug,
 {
+    /// Creates an agent whose prompts describe the game's move input (see GameAction::input_hint()).
     pub fn new(player_color: PlayerColor) -> Self {
+        Self::with_move_hint(player_color, TState::Action::input_hint())
+    }
+
+    /// Creates an agent whose prompts describe the expected move input with the given hint,
+    /// instead of the game's own.
+    pub fn with_move_hint(player_color: PlayerColor, move_hint: impl Into<String>) -> Self {
         Self {
             _phantom: Default::default(),
             player_color,
+            move_hint: move_hint.into(),
         }
     }
 
-    fn prompt_input(&self) -> TState::Action {
+    /// Prompts until the user enters a move, or "undo" to take back their last move
+    /// (if taking back is allowed).
+    fn prompt_input(&self, allow_take_back: bool) -> TurnChoice<TState::Action> {
         use std::io::stdin;
 
-        println!("Enter move x,y: ");
+        if allow_take_back {
+            println!(
+                "Enter move {} (or \"undo\" to take back your last move): ",
+                self.move_hint
+            );
+        } else {
+            println!("Enter move {}: ", self.move_hint);
+        }
 
         let mut input = String::new();
 
@@ -33,13 +54,32 @@ where
             .read_line(&mut input)
             .expect("Couldn't capture user input.");
 
+        if allow_take_back && input.trim() == "undo" {
+            return TurnChoice::TakeBack;
+        }
+
         let result = TState::Action::from_str(&input);
 
         match result {
-            Ok(r) => r,
+            Ok(r) => TurnChoice::Play(r),
             _ => {
                 println!("Invalid input.  Try again.");
-                self.prompt_input()
+                self.prompt_input(allow_take_back)
+            }
+        }
+    }
+
+    fn prompt_turn(
+        &self,
+        legal_moves: &[TState::Action],
+        allow_take_back: bool,
+    ) -> TurnChoice<TState::Action> {
+        loop {
+            match self.prompt_input(allow_take_back) {
+                TurnChoice::Play(user_input) if !legal_moves.contains(&user_input) => {
+                    println!("The provided move was not valid. Try again.");
+                }
+                choice => return choice,
             }
         }
     }
@@ -51,17 +91,20 @@ where
     <TState::Action as FromStr>::Err: std::fmt::Debug,
 {
     fn pick_move(&self, _state: &TState, legal_moves: &[TState::Action]) -> TState::Action {
-        loop {
-            let user_input = self.prompt_input();
-
-            if legal_moves.iter().find(|&&m| m == user_input).is_none() {
-                println!("The provided move was not valid. Try again.");
-            } else {
-                return user_input;
-            }
+        match self.prompt_turn(legal_moves, false) {
+            TurnChoice::Play(action) => action,
+            TurnChoice::TakeBack => unreachable!("Taking back was not offered."),
         }
     }
 
+    fn pick_turn(
+        &self,
+        _state: &TState,
+        legal_moves: &[TState::Action],
+    ) -> TurnChoice<TState::Action> {
+        self.prompt_turn(legal_moves, true)
+    }
+
     fn player_color(&self) -> PlayerColor {
         self.player_color
     }
@@ -4,5 +4,14 @@ mod random_agent;
 mod util;
 
 pub use human_agent::HumanAgent;
-pub use mcts_agent::MctsAgent;
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub use mcts_agent::bench as tree_search_bench;
+pub use mcts_agent::{
+    analyze, analyze_with_config, ChildReport, MctsAgent, MctsConfig, MctsEndCondition,
+    MctsObjective, MoveAnalysis, RaveConfig, SearchReport, WideningConfig,
+};
+pub use monte_carlo_tree::export::{ExportOptions, ExportedNode};
+pub use monte_carlo_tree::persist::PersistError;
+pub use monte_carlo_tree::principal_variation::PvStep;
 pub use random_agent::RandomAgent;
@@ -1,4 +1,12 @@
 mod agent;
+mod analysis;
+mod report;
 mod tree_search;
 
 pub use agent::MctsAgent;
+pub use analysis::{analyze, analyze_with_config, MoveAnalysis};
+pub use report::{ChildReport, SearchReport};
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub use tree_search::bench;
+pub use tree_search::{MctsConfig, MctsEndCondition, MctsObjective, RaveConfig, WideningConfig};
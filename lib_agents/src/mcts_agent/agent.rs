@@ -1,12 +1,21 @@
-use super::tree_search;
-use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use super::report::SearchReport;
+use super::tree_search::{self, MctsConfig};
+use lib_boardgame::{GameAgent, GameResult, GameState, MoveOrdering, PlayerColor, StateEncoding};
 use lib_printer::{out, out_impl};
 use monte_carlo_tree::{
-    arc_tree::ArcNode, monte_carlo_data::MctsData, monte_carlo_data::MctsResult, tree::Node,
+    arc_tree::ArcNode,
+    export::{ExportOptions, ExportedNode},
+    monte_carlo_data::MctsData,
+    monte_carlo_data::MctsResult,
+    persist::{self, PersistError},
+    principal_variation::{self, PvStep},
+    tree::Node,
 };
 use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::io::Write;
 use std::marker::Sync;
+use std::path::Path;
 use std::time::Instant;
 
 pub struct MctsAgent<TState, TNode = ArcNode<MctsData<TState>>>
@@ -15,7 +24,12 @@ where
     TNode: Node<Data = MctsData<TState>>,
 {
     color: PlayerColor,
+    config: MctsConfig,
+    search_count: Cell<u64>,
     current_state_root: RefCell<Option<TNode::Handle>>,
+    last_search_root: RefCell<Option<TNode::Handle>>,
+    last_report: RefCell<Option<SearchReport>>,
+    report_writer: RefCell<Option<Box<dyn Write>>>,
     anticipated_opponent_actions: RefCell<Vec<TState::Action>>,
 }
 
@@ -25,13 +39,69 @@ where
     TNode: Node<Data = MctsData<TState>>,
 {
     pub fn new(color: PlayerColor) -> Self {
+        Self::with_config(color, Default::default())
+    }
+
+    /// Creates an agent that performs MCTS using the given configuration.
+    pub fn with_config(color: PlayerColor, config: MctsConfig) -> Self {
         MctsAgent {
             color,
+            config,
+            search_count: Cell::new(0),
             current_state_root: RefCell::new(None),
+            last_search_root: RefCell::new(None),
+            last_report: RefCell::new(None),
+            report_writer: RefCell::new(None),
             anticipated_opponent_actions: Default::default(),
         }
     }
 
+    /// The report of the most recent search,
+    /// or None if the agent has not picked a move yet.
+    pub fn last_search_report(&self) -> Option<SearchReport> {
+        self.last_report.borrow().clone()
+    }
+
+    /// After every search, the search's report will be written
+    /// to the given writer as a single line of JSON.
+    pub fn write_reports_to<W: Write + 'static>(&self, writer: W) {
+        *self.report_writer.borrow_mut() = Some(Box::new(writer));
+    }
+
+    fn record_report(&self, report: SearchReport) {
+        if let Some(writer) = self.report_writer.borrow_mut().as_mut() {
+            // Telemetry is best-effort, so a failed write must not interrupt the game.
+            if let Err(e) =
+                writeln!(writer, "{}", report.to_json_line()).and_then(|_| writer.flush())
+            {
+                out!("Couldn't write search report: {}", e);
+            }
+        }
+
+        *self.last_report.borrow_mut() = Some(report);
+    }
+
+    /// The line of most-visited actions found by the most recent search,
+    /// starting with the action chosen from the state the agent last picked a move for.
+    /// Win rates are from the perspective of this agent.
+    /// Returns an empty line if the agent has not picked a move yet.
+    pub fn principal_variation(&self) -> Vec<PvStep<TState::Action>> {
+        match self.last_search_root.borrow().as_ref() {
+            Some(root) => principal_variation::principal_variation(root.borrow()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Exports the tree built by the most recent search,
+    /// rooted at the state the agent last picked a move for.
+    /// Returns None if the agent has not picked a move yet.
+    pub fn export_search_tree(&self, options: ExportOptions) -> Option<ExportedNode> {
+        let root = self.last_search_root.borrow();
+        let root = root.as_ref()?;
+
+        Some(ExportedNode::capture(root.borrow(), options))
+    }
+
     fn walk_tree_to_child(&self, action: TState::Action) {
         // IDEA: half threads are "win seekers" and other half is "loss seeker"
         // (i.e. explores as though we're playing for the opponent)
@@ -78,10 +148,50 @@ where
     }
 }
 
+impl<TState, TNode> MctsAgent<TState, TNode>
+where
+    TState: StateEncoding,
+    TNode: Node<Data = MctsData<TState>>,
+{
+    /// Saves the agent's current tree to the given file, so a later run can resume from it.
+    /// Does nothing if the agent has no tree yet.
+    pub fn save_tree(&self, path: impl AsRef<Path>) -> Result<(), PersistError> {
+        match self.current_root_handle() {
+            Some(root) => persist::save_tree_to_file(root.borrow(), self.color, path),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the agent's current tree with one loaded from the given file.
+    /// The loaded tree must be rooted at the given state, which the agent will next
+    /// pick a move for (or observe an action from), and must have been searched
+    /// by a player of the same color.
+    pub fn load_tree(&self, path: impl AsRef<Path>, state: &TState) -> Result<(), PersistError> {
+        let loaded = persist::load_tree_from_file::<TNode, TState>(path)?;
+
+        if loaded.perspective != self.color {
+            return Err(PersistError::Format(format!(
+                "the tree was searched by {:?}, but this agent is {:?}",
+                loaded.perspective, self.color
+            )));
+        }
+
+        if loaded.root.borrow().data().state().encode_state() != state.encode_state() {
+            return Err(PersistError::Format(
+                "the tree is rooted at a different position".to_owned(),
+            ));
+        }
+
+        *self.current_state_root.borrow_mut() = Some(loaded.root);
+
+        Ok(())
+    }
+}
+
 impl<TState, TNode> GameAgent<TState> for MctsAgent<TState, TNode>
 where
     TNode: Node<Data = MctsData<TState>>,
-    TState: GameState + Sync,
+    TState: MoveOrdering + StateEncoding + Sync,
 {
     fn player_color(&self) -> PlayerColor {
         self.color
@@ -111,25 +221,51 @@ where
         }
     }
 
-    fn pick_move(&self, state: &TState, _legal_moves: &[TState::Action]) -> TState::Action {
-        let root_handle = self
+    fn observe_undo(&self, _player: PlayerColor, _action: TState::Action, _result: &TState) {
+        // Step back up to the node for the restored state, if the tree reaches that far;
+        // otherwise the tree is discarded, and rebuilt at the next search.
+        let parent = self
             .current_root_handle()
-            .unwrap_or_else(|| self.reset_root_handle(state));
-        let copy_handle = root_handle.clone();
+            .and_then(|root| root.borrow().parent());
 
-        let result = match self.color {
-            PlayerColor::Black => perform_mcts_par::<TNode, TState>(root_handle, self.color),
-            PlayerColor::White => perform_mcts_par::<TNode, TState>(root_handle, self.color),
+        *self.current_state_root.borrow_mut() = parent;
+    }
+
+    fn pick_move(&self, state: &TState, _legal_moves: &[TState::Action]) -> TState::Action {
+        // A tree rooted anywhere but the given state can't be searched for it, so it's discarded.
+        let root_handle = match self.current_root_handle() {
+            Some(root) if root.borrow().data().state().encode_state() == state.encode_state() => {
+                root
+            }
+            _ => self.reset_root_handle(state),
+        };
+        let copy_handle = root_handle.clone();
+        *self.last_search_root.borrow_mut() = Some(root_handle.clone());
+
+        // Each search gets its own seed, derived from the agent's seed,
+        // so a seeded agent replays the same game given the same opponent moves.
+        let search_count = self.search_count.get();
+        self.search_count.set(search_count + 1);
+
+        let config = MctsConfig {
+            seed: self
+                .config
+                .seed
+                .map(|seed| crate::util::derive_seed(seed, search_count)),
+            ..self.config
         };
 
+        let (result, report) = perform_mcts_par::<TNode, TState>(root_handle, self.color, config);
+        self.record_report(report);
+
         // Find the anticipated opponent responses
         {
             let our_selected_child = copy_handle
                 .borrow()
                 .children_read()
                 .iter()
+                .find(|&n| n.borrow().data().action().unwrap() == result.action)
                 .cloned()
-                .find(|n| n.borrow().data().action().unwrap() == result.action)
                 .unwrap();
 
             let mut opponent_choices = our_selected_child.borrow().children_read().clone();
@@ -166,6 +302,11 @@ where
         };
 
         out!("{}", pretty_ratio_bar_text(20, white_wins, result.plays));
+        out!(
+            "Expected score margin for {:?}: {:+.2}",
+            self.color,
+            result.mean_margin
+        );
 
         result.action
     }
@@ -180,7 +321,7 @@ fn pretty_ratio_bar_text(
 
     text_bar.push_str("B [");
 
-    let bar_len = (numerator_white_wins * len_chars) / denominator_plays;
+    let bar_len = (numerator_white_wins * len_chars) / usize::max(1, denominator_plays);
     let bar_txt = "=".repeat(bar_len);
     text_bar.push_str(&bar_txt);
     text_bar.push('|');
@@ -196,52 +337,75 @@ fn pretty_ratio_bar_text(
 fn perform_mcts_par<TNode, TState>(
     root: TNode::Handle,
     player_color: PlayerColor,
-) -> MctsResult<TState>
+    config: MctsConfig,
+) -> (MctsResult<TState>, SearchReport)
 where
     TNode: Node<Data = MctsData<TState>> + Sync,
-    TState: GameState + Sync,
+    TState: MoveOrdering + Sync,
 {
-    let total_plays_before = root
-        .borrow()
-        .children_read()
-        .iter()
-        .map(|c| c.borrow().data().wins_plays().1)
-        .sum::<usize>();
+    let reused_tree_size = root.borrow().data().tree_size();
 
     let now = Instant::now();
-    let results = tree_search::mcts::<TNode, TState>(root, player_color);
+    let (results, stats) = tree_search::mcts::<TNode, TState>(root.clone(), player_color, config);
     let elapsed = now.elapsed();
 
+    let selected = select_result(&results, player_color);
+    let report = SearchReport::new(
+        root.borrow(),
+        player_color,
+        &selected,
+        &results,
+        stats,
+        elapsed,
+        reused_tree_size,
+    );
+
     // Some friendly UI output
     {
-        let total_plays = results.iter().map(|r| r.plays).sum::<usize>();
-        let total_plays = total_plays - total_plays_before;
-
-        let sims_per_sec = total_plays as f64 / (elapsed.as_millis() as f64 / 1_000_f64);
-        out!("Simulations per sec: {:.0}", sims_per_sec);
+        out!("Simulations per sec: {:.0}", report.rollouts_per_sec());
 
         for action_result in &results {
             out!("{:?}", action_result);
         }
     }
 
+    (selected, report)
+}
+
+/// Selects the result of the action to take, among the results of a search.
+fn select_result<TState>(
+    results: &[MctsResult<TState>],
+    player_color: PlayerColor,
+) -> MctsResult<TState>
+where
+    TState: GameState,
+{
+    let is_proven_loss = |r: &MctsResult<TState>| match r.proven {
+        Some(result) => result != GameResult::Tie && !result.is_win_for_player(player_color),
+        None => false,
+    };
+
     if let Some(winner) = results
         .iter()
-        .find(|r| r.is_saturated && r.worst_wins == r.worst_plays)
+        .filter(|r| r.proven.is_some_and(|p| p.is_win_for_player(player_color)))
+        .max_by_key(|r| r.plays)
     {
-        // If a move gives a guaranteed win, take it.
+        // If a move is proven to win, take it.
         winner.clone()
-    } else if results.iter().all(|r| r.is_saturated) {
-        // Otherwise, if the full tree has been searched,
-        // pick the move with the highest win ratio.
-        let mut results = results.clone();
-        results.sort_by_key(|r| r.worst_wins * 10_000 / r.worst_plays);
-
-        results.pop().unwrap()
+    } else if results.iter().all(is_proven_loss) {
+        // Every move is proven to lose, so the best we can do
+        // is the move that was simulated the most.
+        results
+            .iter()
+            .max_by_key(|r| r.plays)
+            .expect("Must have been a max result")
+            .clone()
     } else {
-        // Otherwise, we pick the move that was simulated the most.
+        // Otherwise, we pick the move that was simulated the most,
+        // among the moves not yet proven to lose.
         results
             .iter()
+            .filter(|r| !is_proven_loss(r))
             .max_by_key(|r| r.plays)
             .expect("Must have been a max result")
             .clone()
@@ -259,8 +423,28 @@ where
 mod tests {
     use super::*;
 
-    use lib_boardgame::GameState;
+    use lib_boardgame::{GameState, Oracle};
+    use lib_connect_four::SizedConnectFourState;
     use lib_tic_tac_toe::tic_tac_toe_gamestate::{BoardPosition, TicTacToeAction, TicTacToeState};
+    use std::path::PathBuf;
+
+    /// A path in the temp directory, unique to this process and test,
+    /// whose file (if any) is deleted when the path is dropped.
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(test_name: &str) -> Self {
+            let name = format!("lib_agents_{}_{}", std::process::id(), test_name);
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            // The test may have failed before creating the file.
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
 
     #[test]
     fn tree_search_always_picks_winning_move() {
@@ -300,10 +484,10 @@ mod tests {
         state.apply_move(TicTacToeAction(BoardPosition::new(1, 1)));
 
         assert_eq!(state.current_player_turn(), PlayerColor::Black);
-        let legal_moves = state.legal_moves(PlayerColor::Black);
+        let legal_moves = state.legal_moves();
 
         let test_black_agent: MctsAgent<_, ArcNode<_>> = MctsAgent::new(PlayerColor::Black);
-        let mcts_chosen_move = test_black_agent.pick_move(&state, &legal_moves);
+        let mcts_chosen_move = test_black_agent.pick_move(&state, legal_moves);
 
         // The agent MUST pick the winning move:
         //  V
@@ -312,4 +496,196 @@ mod tests {
         // __O
         assert_eq!(TicTacToeAction(BoardPosition::new(1, 2)), mcts_chosen_move);
     }
+
+    fn play_seeded_game(seed: u64) -> Vec<TicTacToeAction> {
+        let black: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(seed, 300));
+        let white: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::White, MctsConfig::deterministic(seed + 1, 300));
+
+        let mut state = TicTacToeState::initial_state();
+        let mut moves = Vec::new();
+
+        while !state.is_game_over() {
+            let player = state.current_player_turn();
+            let agent = match player {
+                PlayerColor::Black => &black,
+                PlayerColor::White => &white,
+            };
+
+            let action = agent.pick_move(&state, state.legal_moves());
+            state.apply_move(action);
+            moves.push(action);
+
+            black.observe_action(player, action, &state);
+            white.observe_action(player, action, &state);
+        }
+
+        moves
+    }
+
+    #[test]
+    fn seeded_agents_expect_replay_identical_games() {
+        assert_eq!(play_seeded_game(1234), play_seeded_game(1234));
+    }
+
+    #[test]
+    fn export_search_tree_expects_tree_of_last_search() {
+        let agent: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(5, 200));
+
+        assert!(agent.export_search_tree(ExportOptions::default()).is_none());
+
+        let state = TicTacToeState::initial_state();
+        agent.pick_move(&state, state.legal_moves());
+
+        let exported = agent.export_search_tree(ExportOptions::default()).unwrap();
+
+        assert_eq!(None, exported.action);
+        assert_eq!(9, exported.children.len());
+        assert!(exported.plays > 0);
+    }
+
+    #[test]
+    fn load_tree_expects_resumes_saved_search() {
+        let path = TempPath::new("load_tree.tree");
+        let state = TicTacToeState::initial_state();
+
+        let agent: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(3, 200));
+        agent.pick_move(&state, state.legal_moves());
+        agent.save_tree(&path.0).unwrap();
+
+        let saved_plays = agent.current_root_handle().unwrap().data().wins_plays().1;
+
+        let resumed: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(3, 200));
+        resumed.load_tree(&path.0, &state).unwrap();
+        resumed.pick_move(&state, state.legal_moves());
+
+        let resumed_plays = resumed.current_root_handle().unwrap().data().wins_plays().1;
+        assert!(resumed_plays > saved_plays);
+
+        // A tree searched by Black is meaningless to White.
+        let white: MctsAgent<TicTacToeState, ArcNode<_>> = MctsAgent::new(PlayerColor::White);
+        assert!(white.load_tree(&path.0, &state).is_err());
+
+        // So is a tree rooted at a different position.
+        let other = state.next_state(state.legal_moves()[0]);
+        let black: MctsAgent<TicTacToeState, ArcNode<_>> = MctsAgent::new(PlayerColor::Black);
+        assert!(black.load_tree(&path.0, &other).is_err());
+    }
+
+    #[test]
+    fn pick_move_with_tree_for_other_state_expects_fresh_search() {
+        let state = TicTacToeState::initial_state();
+        let other = state.next_state(state.legal_moves()[0]);
+
+        let agent: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(5, 100));
+        agent.pick_move(&other, other.legal_moves());
+
+        // The tree is still rooted at the other state, which this search must not reuse.
+        agent.pick_move(&state, state.legal_moves());
+
+        let root = agent.current_root_handle().unwrap();
+        assert_eq!(state.encode_state(), root.data().state().encode_state());
+        assert_eq!(9, root.children_read().len());
+    }
+
+    #[test]
+    fn pick_move_expects_writes_search_report() {
+        let path = TempPath::new("search_report.jsonl");
+        let state = TicTacToeState::initial_state();
+
+        let agent: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(8, 300));
+        assert!(agent.last_search_report().is_none());
+
+        agent.write_reports_to(std::fs::File::create(&path.0).unwrap());
+        let action = agent.pick_move(&state, state.legal_moves());
+
+        let report = agent.last_search_report().unwrap();
+        assert_eq!(action.to_string(), report.selected_action);
+        assert_eq!(1, report.threads);
+        assert_eq!(0, report.reused_tree_size);
+        assert_eq!(9, report.children.len());
+        assert!(report.nodes_created > 0);
+        assert!(!report.principal_variation.is_empty());
+        assert_eq!(
+            report.principal_variation,
+            agent
+                .principal_variation()
+                .iter()
+                .map(|step| step.action.to_string())
+                .collect::<Vec<_>>()
+        );
+
+        let written = std::fs::read_to_string(&path.0).unwrap();
+        let lines = written.lines().collect::<Vec<_>>();
+        assert_eq!(1, lines.len());
+        assert_eq!(
+            report,
+            serde_json::from_str::<SearchReport>(lines[0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn observe_undo_expects_returns_to_prior_root() {
+        let mut state = TicTacToeState::initial_state();
+
+        let agent: MctsAgent<_, ArcNode<_>> =
+            MctsAgent::with_config(PlayerColor::Black, MctsConfig::deterministic(2, 200));
+        let action = agent.pick_move(&state, state.legal_moves());
+        let searched_plays = agent.current_root_handle().unwrap().data().wins_plays().1;
+
+        state.apply_move(action);
+        agent.observe_action(PlayerColor::Black, action, &state);
+        assert_eq!(
+            Some(action),
+            agent.current_root_handle().unwrap().data().action()
+        );
+
+        agent.observe_undo(PlayerColor::Black, action, &TicTacToeState::initial_state());
+        let root = agent.current_root_handle().unwrap();
+        assert_eq!(None, root.data().action());
+        assert_eq!(searched_plays, root.data().wins_plays().1);
+
+        // Undoing past the root of the tree discards it.
+        agent.observe_undo(PlayerColor::White, action, &TicTacToeState::initial_state());
+        assert!(agent.current_root_handle().is_none());
+    }
+
+    /// Asserts that a deterministic agent with the given rollouts picks an optimal move
+    /// in every position the oracle solved, wherever some move is worse than the others.
+    fn assert_no_mistakes<T>(oracle: &Oracle<T>, rollouts: usize)
+    where
+        T: MoveOrdering + StateEncoding + Send + Sync,
+        T::Action: Send + Sync,
+    {
+        let mistakes = oracle.mistakes(|color| {
+            MctsAgent::<T, ArcNode<_>>::with_config(color, MctsConfig::deterministic(0, rollouts))
+        });
+
+        assert!(
+            mistakes.is_empty(),
+            "{} mistakes, the first:\n{}",
+            mistakes.len(),
+            mistakes[0]
+        );
+    }
+
+    #[test]
+    fn mcts_expects_optimal_move_in_every_tic_tac_toe_position() {
+        let oracle = Oracle::solve(&TicTacToeState::initial_state());
+
+        assert_no_mistakes(&oracle, 5_000);
+    }
+
+    #[test]
+    fn mcts_expects_optimal_move_in_every_small_connect_four_position() {
+        let oracle = Oracle::solve(&SizedConnectFourState::<4, 3>::initial_state());
+
+        assert_no_mistakes(&oracle, 5_000);
+    }
 }
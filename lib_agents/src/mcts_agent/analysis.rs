@@ -0,0 +1,152 @@
+use super::tree_search::{self, MctsConfig, MctsEndCondition};
+use lib_boardgame::{GameResult, MoveOrdering};
+use monte_carlo_tree::{
+    arc_tree::ArcNode,
+    monte_carlo_data::MctsData,
+    principal_variation::{principal_variation, PvStep},
+    tree::Node,
+};
+use std::cmp::Reverse;
+
+/// The evaluation of a single legal move, as found by analyze().
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveAnalysis<TAction> {
+    pub action: TAction,
+
+    /// The ratio of wins to plays after this move,
+    /// from the perspective of the player making it.
+    pub win_rate: f32,
+
+    /// The mean reward of the simulations after this move, in the range [0, 1].
+    pub mean_reward: f32,
+
+    /// The mean final score margin after this move,
+    /// from the perspective of the player making it.
+    pub mean_margin: f32,
+
+    pub plays: usize,
+
+    /// The share of the root's plays that went to this move.
+    pub visit_share: f32,
+
+    /// The result of the game under perfect play after this move, if the search proved it.
+    pub proven: Option<GameResult>,
+
+    /// The line of most-visited actions, starting with this move.
+    pub principal_variation: Vec<PvStep<TAction>>,
+}
+
+/// Searches the given state, on behalf of the player to move, until the budget is spent,
+/// and evaluates every legal move. The moves are ordered most played first.
+pub fn analyze<TState>(
+    state: &TState,
+    budget: MctsEndCondition,
+) -> Vec<MoveAnalysis<TState::Action>>
+where
+    TState: MoveOrdering,
+    MctsData<TState>: Send + Sync,
+{
+    let config = MctsConfig {
+        end_condition: budget,
+        ..Default::default()
+    };
+
+    analyze_with_config(state, config)
+}
+
+/// Like analyze(), but searches with the given configuration.
+pub fn analyze_with_config<TState>(
+    state: &TState,
+    config: MctsConfig,
+) -> Vec<MoveAnalysis<TState::Action>>
+where
+    TState: MoveOrdering,
+    MctsData<TState>: Send + Sync,
+{
+    let player_color = state.current_player_turn();
+    let root = ArcNode::new_root(MctsData::new(state.clone(), 0, 0, None));
+
+    tree_search::mcts::<ArcNode<_>, TState>(root.clone(), player_color, config);
+
+    let children = root.children_read();
+    let total_plays = children
+        .iter()
+        .map(|c| c.data().wins_plays().1)
+        .sum::<usize>();
+
+    let mut analysis = children
+        .iter()
+        .map(|child| {
+            let data = child.data();
+            let (wins, plays) = data.wins_plays();
+
+            let first = PvStep {
+                action: data.action().expect("A child must have an action."),
+                wins,
+                plays,
+            };
+
+            let mut pv = vec![first];
+            pv.extend(principal_variation(child));
+
+            MoveAnalysis {
+                action: first.action,
+                win_rate: first.win_rate(),
+                mean_reward: data.mean_reward(),
+                mean_margin: data.mean_margin(),
+                plays,
+                visit_share: plays as f32 / usize::max(1, total_plays) as f32,
+                proven: data.proven_result(),
+                principal_variation: pv,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    analysis.sort_by_key(|a| Reverse(a.plays));
+
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_boardgame::{GameState, PlayerColor};
+    use lib_tic_tac_toe::tic_tac_toe_gamestate::{BoardPosition, TicTacToeAction, TicTacToeState};
+
+    #[test]
+    fn analyze_expects_every_legal_move_evaluated() {
+        let state = TicTacToeState::initial_state();
+
+        let analysis = analyze_with_config(&state, MctsConfig::deterministic(4, 500));
+
+        assert_eq!(9, analysis.len());
+
+        let share_sum = analysis.iter().map(|a| a.visit_share).sum::<f32>();
+        assert!((share_sum - 1.0).abs() < 0.001);
+
+        assert!(analysis.windows(2).all(|w| w[0].plays >= w[1].plays));
+        assert!(analysis
+            .iter()
+            .all(|a| a.principal_variation[0].action == a.action));
+    }
+
+    #[test]
+    fn analyze_expects_proves_winning_move() {
+        // X X -
+        // O O -
+        // - - -
+        let mut state = TicTacToeState::initial_state();
+        for &(col, row) in &[(0, 2), (0, 1), (1, 2), (1, 1)] {
+            state.apply_move(TicTacToeAction(BoardPosition::new(col, row)));
+        }
+        assert_eq!(PlayerColor::Black, state.current_player_turn());
+
+        let analysis = analyze(&state, MctsEndCondition::RolloutCount(2_000));
+
+        let winning = TicTacToeAction(BoardPosition::new(2, 2));
+        let win = analysis.iter().find(|a| a.action == winning).unwrap();
+
+        assert_eq!(Some(GameResult::BlackWins), win.proven);
+        assert_eq!(1.0, win.win_rate);
+    }
+}
@@ -0,0 +1,118 @@
+use super::tree_search::SearchStats;
+use lib_boardgame::{GameResult, GameState, PlayerColor};
+use monte_carlo_tree::{
+    monte_carlo_data::MctsData, monte_carlo_data::MctsResult,
+    principal_variation::principal_variation, tree::Node,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The statistics of a single root action, as seen at the end of a search.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChildReport {
+    pub action: String,
+    pub wins: usize,
+    pub plays: usize,
+    pub mean_reward: f32,
+    pub mean_margin: f32,
+    pub tree_size: usize,
+    pub is_saturated: bool,
+    pub proven: Option<String>,
+}
+
+impl<TState: GameState> From<&MctsResult<TState>> for ChildReport {
+    fn from(result: &MctsResult<TState>) -> Self {
+        Self {
+            action: result.action.to_string(),
+            wins: result.wins,
+            plays: result.plays,
+            mean_reward: result.mean_reward,
+            mean_margin: result.mean_margin,
+            tree_size: result.tree_size,
+            is_saturated: result.is_saturated,
+            proven: result.proven.map(result_name),
+        }
+    }
+}
+
+/// A structured summary of a single search performed by an MctsAgent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SearchReport {
+    /// The player the search was performed for.
+    pub player: String,
+
+    /// The action the agent selected.
+    pub selected_action: String,
+
+    pub elapsed_ms: u64,
+    pub rollouts: usize,
+    pub threads: usize,
+
+    /// The count of nodes added to the tree by this search.
+    pub nodes_created: usize,
+
+    /// The size of the tree carried over from earlier searches, before this search began.
+    pub reused_tree_size: usize,
+
+    /// The greatest depth below the root reached by selection.
+    pub max_depth: usize,
+
+    /// The mean depth below the root reached by selection, per rollout.
+    pub average_depth: f32,
+
+    /// The line of most-played actions from the root.
+    pub principal_variation: Vec<String>,
+
+    /// The statistics of every root action, most played first.
+    pub children: Vec<ChildReport>,
+}
+
+impl SearchReport {
+    pub(super) fn new<TNode, TState>(
+        root: &TNode,
+        player: PlayerColor,
+        selected: &MctsResult<TState>,
+        results: &[MctsResult<TState>],
+        stats: SearchStats,
+        elapsed: Duration,
+        reused_tree_size: usize,
+    ) -> Self
+    where
+        TNode: Node<Data = MctsData<TState>>,
+        TState: GameState,
+    {
+        let mut children = results.iter().map(ChildReport::from).collect::<Vec<_>>();
+        children.sort_by_key(|c| std::cmp::Reverse(c.plays));
+
+        Self {
+            player: format!("{:?}", player),
+            selected_action: selected.action.to_string(),
+            elapsed_ms: elapsed.as_millis() as u64,
+            rollouts: stats.rollouts,
+            threads: stats.thread_count,
+            nodes_created: root.data().tree_size().saturating_sub(reused_tree_size),
+            reused_tree_size,
+            max_depth: stats.max_depth,
+            average_depth: stats.average_depth(),
+            principal_variation: principal_variation(root)
+                .iter()
+                .map(|step| step.action.to_string())
+                .collect(),
+            children,
+        }
+    }
+
+    /// The rate of rollouts performed per second of search.
+    pub fn rollouts_per_sec(&self) -> f64 {
+        self.rollouts as f64 / (u64::max(1, self.elapsed_ms) as f64 / 1_000_f64)
+    }
+
+    /// The report as a single line of JSON, with no trailing newline.
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("A search report is always serializable.")
+    }
+}
+
+fn result_name(result: GameResult) -> String {
+    format!("{:?}", result)
+}
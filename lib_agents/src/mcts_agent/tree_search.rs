@@ -5,7 +5,7 @@ use std::time::{Duration, Instant};
 use crossbeam::thread;
 
 use crate::util;
-use lib_boardgame::{GameResult, GameState, PlayerColor};
+use lib_boardgame::{GameResult, GameState, MoveOrdering, PlayerColor};
 use lib_printer::{out, out_impl};
 use monte_carlo_tree::{monte_carlo_data::MctsData, monte_carlo_data::MctsResult, tree::Node};
 
@@ -26,19 +26,220 @@ mod configs {
 /// (e.x., "mcts is done after 10_000 rollouts have completed")
 /// and by execution time in ms
 /// (e.x., "mcts is done after 12_000 ms of execution time")
-#[derive(Copy, Clone, Debug)]
-enum MctsEndCondition {
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MctsEndCondition {
     /// End MCTS as soon as the given count of rollouts has been performed.
+    /// Each thread performs this many rollouts.
     RolloutCount(usize),
 
     /// End MCTS as soon as it has executed for longer than this duration.
     ExecutionTime(Duration),
 }
 
+/// The quantity MCTS tries to maximize for the player it is acting as.
+/// Every objective produces a reward in the range [0, 1] for each simulation,
+/// which is backpropagated and used to score nodes during traversal.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum MctsObjective {
+    /// A simulation is worth 1 if it ends in a win, and 0 otherwise.
+    #[default]
+    WinLoss,
+
+    /// A simulation is worth its final score margin (see `GameState::player_score`),
+    /// normalized so that the largest possible loss is 0 and the largest possible win is 1.
+    ScoreMargin,
+
+    /// A weighted blend of `WinLoss` and `ScoreMargin`.
+    /// The value is the weight (from 0 to 1) given to the score margin.
+    Blend(f32),
+}
+
+/// Options controlling how MCTS is performed.
+#[derive(Copy, Clone, Debug)]
+pub struct MctsConfig {
+    /// What each simulation is worth to the player performing MCTS.
+    pub objective: MctsObjective,
+
+    /// When true, MCTS behaves as an MCTS-Solver: nodes whose game result has been proven
+    /// are never selected again, and the search ends as soon as the root is proven.
+    /// Proven results are always tracked, even when this is false.
+    pub solver: bool,
+
+    /// When set, All-Moves-As-First statistics are gathered during simulation
+    /// and blended into the selection score (RAVE).
+    pub rave: Option<RaveConfig>,
+
+    /// When set, a node's children are introduced to selection progressively
+    /// (in order of MoveOrdering priority) as the node's play count grows.
+    /// Otherwise, every child may be selected as soon as the node is expanded.
+    pub widening: Option<WideningConfig>,
+
+    /// Determines when MCTS has completed.
+    pub end_condition: MctsEndCondition,
+
+    /// The count of threads performing MCTS,
+    /// or None to use the default for the player's color.
+    pub thread_count: Option<usize>,
+
+    /// When set, every thread's rng is derived from this seed, instead of from entropy.
+    /// Only a single-threaded search with a RolloutCount end condition is fully reproducible.
+    pub seed: Option<u64>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            objective: Default::default(),
+            solver: true,
+            rave: None,
+            widening: None,
+            end_condition: MctsEndCondition::ExecutionTime(Duration::from_millis(5_000)),
+            thread_count: None,
+            seed: None,
+        }
+    }
+}
+
+impl MctsConfig {
+    /// A single-threaded config that performs exactly the given count of rollouts,
+    /// with all randomness derived from the given seed,
+    /// so the same search always builds a bit-identical tree.
+    pub fn deterministic(seed: u64, rollouts: usize) -> Self {
+        Self {
+            end_condition: MctsEndCondition::RolloutCount(rollouts),
+            thread_count: Some(1),
+            seed: Some(seed),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for Rapid Action Value Estimation.
+/// A node's AMAF estimate is blended with its direct estimate using the weight
+/// `beta = sqrt(k / (3n + k))`, where `n` is the node's play count and `k` is the equivalence,
+/// so the AMAF estimate dominates early and fades as direct plays accumulate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RaveConfig {
+    /// The play count at which the AMAF and direct estimates are weighted equally.
+    pub equivalence: f32,
+}
+
+impl Default for RaveConfig {
+    fn default() -> Self {
+        Self {
+            equivalence: 1_000.0,
+        }
+    }
+}
+
+impl RaveConfig {
+    /// The weight given to the AMAF estimate for a node with the given play count.
+    fn beta(self, plays: f32) -> f32 {
+        let k = self.equivalence;
+
+        f32::sqrt(k / (3.0 * plays + k))
+    }
+}
+
+/// Options for progressive widening.
+/// A node with `n` plays allows selection among its first `ceil(coefficient * n^exponent)`
+/// children that are still worth exploring (at least one).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WideningConfig {
+    pub coefficient: f32,
+    pub exponent: f32,
+}
+
+impl Default for WideningConfig {
+    fn default() -> Self {
+        Self {
+            coefficient: 2.0,
+            exponent: 0.5,
+        }
+    }
+}
+
+impl WideningConfig {
+    /// The count of children selectable from a node with the given play count.
+    fn child_limit(self, plays: usize) -> usize {
+        let limit = self.coefficient * (plays as f32).powf(self.exponent);
+
+        usize::max(1, limit.ceil() as usize)
+    }
+}
+
+/// The outcome of a single simulation, from the perspective of the player performing MCTS.
+#[derive(Copy, Clone, Debug)]
+struct SimOutcome {
+    /// True if the simulation ended in a win.
+    is_win: bool,
+
+    /// The reward for the simulation, as determined by the MctsObjective.
+    reward: f32,
+
+    /// The player's final score minus the opponent's final score.
+    margin: isize,
+}
+
+impl SimOutcome {
+    /// Evaluates the final state of a simulation for the given player.
+    fn from_final_state<TState: GameState>(
+        state: &TState,
+        player_color: PlayerColor,
+        objective: MctsObjective,
+    ) -> Self {
+        let is_win = state
+            .game_result()
+            .expect("There must be a game result, since the simulation has completed.")
+            .is_win_for_player(player_color);
+
+        let player_score = state.player_score(player_color);
+        let opponent_score = state.player_score(player_color.opponent());
+        let margin = player_score as isize - opponent_score as isize;
+
+        let win_reward = if is_win { 1.0 } else { 0.0 };
+        let margin_reward = normalize_margin(margin, player_score + opponent_score);
+
+        let reward = match objective {
+            MctsObjective::WinLoss => win_reward,
+            MctsObjective::ScoreMargin => margin_reward,
+            MctsObjective::Blend(weight) => (1.0 - weight) * win_reward + weight * margin_reward,
+        };
+
+        Self {
+            is_win,
+            reward,
+            margin,
+        }
+    }
+
+    /// An outcome that only knows whether it was a win, with no score information.
+    #[cfg(test)]
+    fn from_win(is_win: bool) -> Self {
+        Self {
+            is_win,
+            reward: if is_win { 1.0 } else { 0.0 },
+            margin: 0,
+        }
+    }
+}
+
+/// Maps a score margin onto [0, 1], where 0 means the opponent holds every point,
+/// 1 means the player holds every point, and 0.5 is an even score.
+fn normalize_margin(margin: isize, total_score: usize) -> f32 {
+    if total_score == 0 {
+        return 0.5;
+    }
+
+    0.5 + 0.5 * (margin as f32 / total_score as f32)
+}
+
+/// Creates a child for every legal action of the node's state,
+/// ordered from the highest MoveOrdering priority to the lowest.
 fn expand<TNode, TState>(node: &TNode) -> Result<(), &str>
 where
     TNode: Node<Data = MctsData<TState>>,
-    TState: GameState,
+    TState: MoveOrdering,
 {
     // Acquire the write lock on the children
     // TODO: this should have a "try_acquire_lock()", since
@@ -62,19 +263,24 @@ where
             return Ok(());
         }
 
-        // TODO: There's no reason for legal_moves() to need this argument
-        // since the state already knows the player's turn.
-        let player_turn = state.current_player_turn();
-        let legal_actions = state.legal_moves(player_turn);
+        let legal_actions = state.legal_moves();
 
         // Now that we've expanded this node, update it to
         // inform it how many children it has.
         node.data().set_children_count(legal_actions.len());
         backprop_increment_tree_size(node, legal_actions.len());
 
-        let new_children = legal_actions
-            .iter()
-            .map(|&a| node.new_child(MctsData::new(state.next_state(a), 0, 0, Some(a))))
+        let mut ordered_actions = legal_actions.to_vec();
+        ordered_actions.sort_by(|&a, &b| {
+            let a_priority = state.move_priority(a);
+            let b_priority = state.move_priority(b);
+
+            b_priority.total_cmp(&a_priority)
+        });
+
+        let new_children = ordered_actions
+            .into_iter()
+            .map(|a| node.new_child(MctsData::new(state.next_state(a), 0, 0, Some(a))))
             .collect::<Vec<_>>();
 
         children_write_lock.write(new_children);
@@ -144,6 +350,91 @@ where
     }
 }
 
+/// Given a node whose game result was just proven,
+/// prove as many of its ancestors as possible:
+///     * a parent is proven a win for its mover if any child is a proven win for that mover
+///     * otherwise, once every child of a parent is proven, the parent is proven
+///       to be the best of those results for its mover (win, then tie, then loss)
+fn backprop_proven<TNode, TState>(proven: &TNode)
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: GameState,
+{
+    let mut result = proven
+        .data()
+        .proven_result()
+        .expect("Only a proven node can have its proven status backpropagated.");
+
+    let mut handle = proven.parent();
+
+    while let Some(p) = handle {
+        let node = p.borrow();
+        let data = node.data();
+
+        if data.is_proven() {
+            // Another thread (or a sibling) already proved this node.
+            return;
+        }
+
+        let mover = data.state().current_player_turn();
+
+        let parent_result = if result.is_win_for_player(mover) {
+            Some(result)
+        } else {
+            best_proven_result_for(node, mover)
+        };
+
+        match parent_result {
+            Some(r) => {
+                data.set_proven_result(r);
+                result = r;
+            }
+            None => return,
+        }
+
+        handle = node.parent();
+    }
+}
+
+/// If every child of the given node is proven, returns the best of
+/// the children's results for the given player, otherwise None.
+fn best_proven_result_for<TNode, TState>(node: &TNode, player: PlayerColor) -> Option<GameResult>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: GameState,
+{
+    if !node.data().is_expanded() {
+        return None;
+    }
+
+    let children = node.children_read();
+    let mut best: Option<GameResult> = None;
+
+    for child in children.iter() {
+        let child_result = child.borrow().data().proven_result()?;
+
+        let is_better = match best {
+            None => true,
+            Some(b) => result_rank(child_result, player) > result_rank(b, player),
+        };
+
+        if is_better {
+            best = Some(child_result);
+        }
+    }
+
+    best
+}
+
+/// Orders game results by how good they are for the given player.
+fn result_rank(result: GameResult, player: PlayerColor) -> u8 {
+    match result {
+        GameResult::Tie => 1,
+        r if r.is_win_for_player(player) => 2,
+        _ => 0,
+    }
+}
+
 // TODO: this same work can be done while we are already doing increment_saturation_count()
 fn backprop_terminal_count<TNode, TState>(leaf: &TNode, is_win: bool)
 where
@@ -174,9 +465,9 @@ where
 }
 
 /// Starting with the given node,
-/// increment the node's wins/plays counts based on is_win,
+/// increment the node's wins/plays counts and reward based on the outcome,
 /// and backprop this result up to the root.
-fn backprop_sim_result<TNode, TState>(node: &TNode, is_win: bool)
+fn backprop_sim_result<TNode, TState>(node: &TNode, outcome: SimOutcome)
 where
     TNode: Node<Data = MctsData<TState>>,
     TState: GameState,
@@ -187,12 +478,17 @@ where
         let node_to_update = n.borrow();
         let data = node_to_update.data();
 
+        // The play is counted before the win and reward, and readers load those before
+        // the plays, so a concurrent reader never sees more wins (or reward) than plays.
         data.increment_plays();
 
-        if is_win {
+        if outcome.is_win {
             data.increment_wins();
         }
 
+        data.add_reward(outcome.reward);
+        data.add_margin(outcome.margin);
+
         handle = node_to_update.parent();
     }
 }
@@ -214,7 +510,52 @@ where
     }
 }
 
-fn simulate<TNode, TState, R>(node: &TNode, rng: &mut R) -> GameResult
+/// Backpropagates All-Moves-As-First statistics for a simulation that started at the given node.
+/// `moves` holds every move played during the simulation, along with the player who played it.
+/// At each node from the given node up to the root, every child whose action was played
+/// (at any later point, by the player to move at that node) is credited with the reward.
+fn backprop_amaf<TNode, TState>(
+    node: &TNode,
+    mut moves: Vec<(PlayerColor, TState::Action)>,
+    reward: f32,
+) where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: GameState,
+{
+    let mut handle = Some(node.get_handle());
+    let mut action_taken: Option<TState::Action> = None;
+
+    while let Some(n) = handle {
+        let node_to_update = n.borrow();
+        let mover = node_to_update.data().state().current_player_turn();
+
+        // The action leading down from this node was played by this node's mover.
+        if let Some(action) = action_taken {
+            moves.push((mover, action));
+        }
+
+        for child in node_to_update.children_read().iter() {
+            let child_data = child.borrow().data();
+            let child_action = child_data.action().expect("A child must have an action.");
+
+            if moves.iter().any(|&(p, a)| p == mover && a == child_action) {
+                child_data.add_amaf_play(reward);
+            }
+        }
+
+        action_taken = node_to_update.data().action();
+        handle = node_to_update.parent();
+    }
+}
+
+/// Plays random moves from the node's state until the game is over,
+/// and returns the final state.
+/// If a history is given, every move played is recorded in it, along with its player.
+fn simulate<TNode, TState, R>(
+    node: &TNode,
+    rng: &mut R,
+    mut history: Option<&mut Vec<(PlayerColor, TState::Action)>>,
+) -> TState
 where
     TNode: Node<Data = MctsData<TState>>,
     TState: GameState,
@@ -224,14 +565,16 @@ where
 
     loop {
         if state.is_game_over() {
-            return state
-                .game_result()
-                .expect("There must be a game result, since the game is confirmed to be over.");
+            return state;
         }
 
         let player = state.current_player_turn();
-        let legal_moves = state.legal_moves(player);
-        let random_action = util::random_choice(&legal_moves, rng);
+        let legal_moves = state.legal_moves();
+        let random_action = util::random_choice(legal_moves, rng);
+
+        if let Some(history) = history.as_mut() {
+            history.push((player, random_action));
+        }
 
         state.apply_move(random_action);
     }
@@ -240,32 +583,40 @@ where
 /// Selects using max UCB, but on opponent's turn inverts the score.
 /// If the given node has no unsaturated children,
 /// returns a handle back to the given node.
+/// Also returns the depth of the selected node below the given node.
 fn select_to_leaf<TNode, TState>(
     root: &TNode,
     player_color: PlayerColor,
     jitter: f32,
-) -> TNode::Handle
+    config: MctsConfig,
+) -> (TNode::Handle, usize)
 where
     TNode: Node<Data = MctsData<TState>>,
     TState: GameState,
 {
     let mut cur_node = root.get_handle();
+    let mut depth = 0;
 
     while let Some(c) =
-        select_child_for_traversal::<TNode, TState>(cur_node.borrow(), player_color, jitter)
+        select_child_for_traversal::<TNode, TState>(cur_node.borrow(), player_color, jitter, config)
     {
         cur_node = c;
+        depth += 1;
     }
 
-    cur_node
+    (cur_node, depth)
 }
 
 /// Returns a handle to the child with the greatest selection score,
-/// or None if there are no children OR all children have been saturated.
+/// or None if there are no children OR all children have been saturated
+/// (or, when acting as a solver, proven).
+/// Ties go to the earlier child, so unvisited children are tried in MoveOrdering order.
+/// With progressive widening, only the first few children still worth exploring are considered.
 fn select_child_for_traversal<TNode, TState>(
     root: &TNode,
     player_color: PlayerColor,
     jitter: f32,
+    config: MctsConfig,
 ) -> Option<TNode::Handle>
 where
     TNode: Node<Data = MctsData<TState>>,
@@ -283,22 +634,38 @@ where
         PlayerColor::White => configs::WHITE_FILTER_SAT,
     };
 
+    let child_limit = config
+        .widening
+        .map_or(usize::MAX, |w| w.child_limit(parent_plays));
+
     (*child_nodes)
         .iter()
         .filter(|&n| !filter_sat || !n.borrow().data().is_saturated())
+        .filter(|&n| !config.solver || !n.borrow().data().is_proven())
         // .filter(|&n| {
         //     let (wwins, _wplays) = n.borrow().data().worst_case_wins_plays();
         //     _wplays == 0 || wwins != 0
         // })
-        .max_by(|&a, &b| {
-            let a_score =
-                score_node_for_traversal(a.borrow(), parent_plays, parent_is_player_color, jitter);
-            let b_score =
-                score_node_for_traversal(b.borrow(), parent_plays, parent_is_player_color, jitter);
+        .take(child_limit)
+        .map(|n| {
+            let score = score_node_for_traversal(
+                n.borrow(),
+                parent_plays,
+                parent_is_player_color,
+                jitter,
+                config.rave,
+            );
 
-            a_score.partial_cmp(&b_score).unwrap()
+            (n, score)
         })
-        .cloned()
+        .fold(
+            None,
+            |best: Option<(&TNode::Handle, f32)>, (n, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((n, score)),
+            },
+        )
+        .map(|(n, _)| n.clone())
 }
 
 fn score_node_for_traversal<TNode, TState>(
@@ -306,6 +673,7 @@ fn score_node_for_traversal<TNode, TState>(
     parent_plays: usize,
     parent_is_player_color: bool,
     jitter: f32,
+    rave: Option<RaveConfig>,
 ) -> f32
 where
     TNode: Node<Data = MctsData<TState>>,
@@ -313,31 +681,47 @@ where
 {
     let data = node.data();
 
-    let (mut wins, plays) = {
-        let (w, p) = data.wins_plays();
-        (w as f32, p as f32)
+    let (mut reward, plays) = {
+        let (r, p) = data.reward_plays();
+        (r as f32, p as f32)
     };
 
     if plays == 0f32 {
-        return std::f32::MAX;
+        return f32::MAX;
     }
 
     let (wwins, wplays) = data.worst_case_wins_plays();
     if wplays > 0 && wwins == 0 {
         // the worst case is a loss. don't take it.
-        return std::f32::MIN;
+        return f32::MIN;
     }
 
     // Experiment
-    wins = if parent_is_player_color {
-        wins
+    reward = if parent_is_player_color {
+        reward
     } else {
-        (plays - wins) as f32
+        plays - reward
     };
 
     let parent_plays = parent_plays as f32;
 
-    let node_mean_val = wins / plays;
+    let mut node_mean_val = reward / plays;
+
+    if let Some(rave) = rave {
+        let (amaf_reward, amaf_plays) = data.amaf_reward_plays();
+
+        if amaf_plays > 0 {
+            let amaf_mean_val = (amaf_reward / amaf_plays as f64) as f32;
+            let amaf_mean_val = if parent_is_player_color {
+                amaf_mean_val
+            } else {
+                1.0 - amaf_mean_val
+            };
+
+            let beta = rave.beta(plays);
+            node_mean_val = (1.0 - beta) * node_mean_val + beta * amaf_mean_val;
+        }
+    }
 
     let explore_bias = 3.00 * (1. + jitter);
 
@@ -345,25 +729,65 @@ where
 
     if score.is_nan() {
         panic!(
-            "plays: {}\nwins: {}\nparent_plays: {}\nparent_is_player_color: {}",
-            plays, wins, parent_plays, parent_is_player_color
+            "plays: {}\nreward: {}\nparent_plays: {}\nparent_is_player_color: {}",
+            plays, reward, parent_plays, parent_is_player_color
         );
     }
 
     score
 }
 
+/// Counters describing the work done by a single MCTS execution.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    /// The count of rollouts performed, across all threads.
+    pub rollouts: usize,
+
+    /// The count of threads the search ran on.
+    pub thread_count: usize,
+
+    /// The greatest depth below the root reached by selection.
+    pub max_depth: usize,
+
+    /// The sum of the depths reached by selection in every rollout.
+    pub total_depth: usize,
+}
+
+impl SearchStats {
+    /// The mean depth below the root reached by selection, per rollout.
+    pub fn average_depth(&self) -> f32 {
+        self.total_depth as f32 / usize::max(1, self.rollouts) as f32
+    }
+
+    fn record_rollout(&mut self, depth: usize) {
+        self.rollouts += 1;
+        self.max_depth = usize::max(self.max_depth, depth);
+        self.total_depth += depth;
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            rollouts: self.rollouts + other.rollouts,
+            thread_count: self.thread_count + other.thread_count,
+            max_depth: usize::max(self.max_depth, other.max_depth),
+            total_depth: self.total_depth + other.total_depth,
+        }
+    }
+}
+
 /// Execute MCTS for the given node,
 /// acting as the given player color.
 /// Returns a vec of results (one per next
-/// possible state).
+/// possible state), along with the counters
+/// describing the work done by the search.
 pub fn mcts<TNode, TState>(
     root_handle: TNode::Handle,
     player_color: PlayerColor,
-) -> Vec<MctsResult<TState>>
+    config: MctsConfig,
+) -> (Vec<MctsResult<TState>>, SearchStats)
 where
     TNode: Node<Data = MctsData<TState>>,
-    TState: GameState,
+    TState: MoveOrdering,
 {
     let root = root_handle.borrow();
 
@@ -373,10 +797,10 @@ where
         out!("Beginning mcts on node with wins/plays: {}/{}", wins, plays);
     }
 
-    let thread_count = match player_color {
+    let thread_count = config.thread_count.unwrap_or(match player_color {
         PlayerColor::Black => configs::BLACK_THREAD_COUNT,
         PlayerColor::White => configs::WHITE_THREAD_COUNT,
-    };
+    });
 
     let jitter = if thread_count == 1 {
         0.00
@@ -387,15 +811,13 @@ where
         }
     };
 
-    let end_condition = MctsEndCondition::ExecutionTime(Duration::from_millis(5_000));
-
-    mcts_executor(root, player_color, thread_count, jitter, end_condition);
+    let stats = mcts_executor(root, player_color, thread_count, jitter, config);
 
     let mut state_children = root.children_read().iter().cloned().collect::<Vec<_>>();
 
     state_children.sort_by_key(|c| {
         let (wins, plays) = c.borrow().data().wins_plays();
-        (wins * 10000) / plays
+        (wins * 10000) / usize::max(1, plays)
     });
 
     // Regardless of any other metric, actions that win the game are always preferred.
@@ -407,10 +829,12 @@ where
         }
     });
 
-    state_children
+    let results = state_children
         .into_iter()
         .map(|c| c.borrow().data().into())
-        .collect()
+        .collect();
+
+    (results, stats)
 }
 
 fn mcts_executor<TNode, TState>(
@@ -418,49 +842,67 @@ fn mcts_executor<TNode, TState>(
     player_color: PlayerColor,
     thread_count: usize,
     jitter: f32,
-    end_condition: MctsEndCondition,
-) where
+    config: MctsConfig,
+) -> SearchStats
+where
     TNode: Node<Data = MctsData<TState>>,
-    TState: GameState,
+    TState: MoveOrdering,
 {
+    // Each thread gets its own rng, derived from the configured seed (if any).
+    let thread_seed = |i: usize| config.seed.map(|seed| util::derive_seed(seed, i as u64));
+
     if thread_count == 1 {
         let jitter_result = 0.00;
-        mcts_loop(root, player_color, jitter_result, end_condition);
+        let rng = util::get_rng(thread_seed(0));
+        mcts_loop(root, player_color, jitter_result, config, rng)
     } else {
         // Each thread gets this much explore jitter
         let jitter_chunk_size = jitter / (thread_count as f32);
 
         thread::scope(|s| {
-            for i in 0..thread_count {
-                let jitter_result = (i as f32) * jitter_chunk_size;
-                let jitter_result = jitter_result - (jitter / 2.00);
+            let handles = (0..thread_count)
+                .map(|i| {
+                    let jitter_result = (i as f32) * jitter_chunk_size;
+                    let jitter_result = jitter_result - (jitter / 2.00);
 
-                s.spawn(move |_| {
-                    mcts_loop(root, player_color, jitter_result, end_condition);
-                });
-            }
+                    let rng = util::get_rng(thread_seed(i));
+
+                    s.spawn(move |_| mcts_loop(root, player_color, jitter_result, config, rng))
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("An MCTS thread panicked."))
+                .fold(SearchStats::default(), SearchStats::merge)
         })
-        .unwrap();
+        .unwrap()
     }
 }
 
-fn mcts_loop<TNode, TState>(
+fn mcts_loop<TNode, TState, R>(
     root: &TNode,
     player_color: PlayerColor,
     jitter: f32,
-    end_condition: MctsEndCondition,
-) where
+    config: MctsConfig,
+    mut rng: R,
+) -> SearchStats
+where
     TNode: Node<Data = MctsData<TState>>,
-    TState: GameState,
+    TState: MoveOrdering,
+    R: rand::Rng,
 {
     let now = Instant::now();
-    let mut rng = util::get_rng();
     let mut rollouts = 0;
+    let mut stats = SearchStats {
+        thread_count: 1,
+        ..Default::default()
+    };
 
     loop {
         rollouts += 1;
 
-        match end_condition {
+        match config.end_condition {
             MctsEndCondition::ExecutionTime(duration) => {
                 if now.elapsed() >= duration {
                     break;
@@ -473,21 +915,30 @@ fn mcts_loop<TNode, TState>(
             }
         }
 
-        if root.data().is_saturated() {
+        if root.data().is_saturated() || (config.solver && root.data().is_proven()) {
             break;
         }
 
-        let leaf = select_to_leaf(root, player_color, jitter);
+        let (leaf, depth) = select_to_leaf(root, player_color, jitter, config);
         let leaf = leaf.borrow();
 
         let expand_result = expand(leaf);
 
         if expand_result.is_err() {
-            // another thread beat us to expanding,
-            // so just continue with a new leaf selection
+            // Usually another thread beat us to expanding, so a new selection goes deeper.
+            // But if every child is filtered out of selection, a new selection would
+            // return this same leaf again, so settle it first.
+            if !leaf.children_read().is_empty()
+                && select_child_for_traversal(leaf, player_color, jitter, config).is_none()
+            {
+                settle_filtered_leaf(leaf);
+            }
+
             continue;
         }
 
+        stats.record_rollout(depth);
+
         let expanded_children = leaf.children_read();
 
         if !expanded_children.is_empty() {
@@ -499,17 +950,27 @@ fn mcts_loop<TNode, TState>(
                 sim_node.data().get_lock(),
                 || sim_node.data().wins_plays().1 == 0,
                 || {
-                    let sim_result = simulate(sim_node, &mut rng);
+                    let mut history = config.rave.map(|_| Vec::new());
+                    let final_state = simulate(sim_node, &mut rng, history.as_mut());
+                    let outcome =
+                        SimOutcome::from_final_state(&final_state, player_color, config.objective);
 
-                    let is_win = sim_result.is_win_for_player(player_color);
-                    backprop_sim_result(sim_node, is_win);
+                    backprop_sim_result(sim_node, outcome);
+
+                    if let Some(history) = history {
+                        backprop_amaf(sim_node, history, outcome.reward);
+                    }
                 },
             );
         } else {
             // We expanded the node, but it had no children,
             // so this node must be a terminating node.
-            let sim_result = simulate(leaf, &mut rng);
-            let is_win = sim_result.is_win_for_player(player_color);
+            let final_state = simulate(leaf, &mut rng, None);
+            let outcome =
+                SimOutcome::from_final_state(&final_state, player_color, config.objective);
+            let sim_result = final_state
+                .game_result()
+                .expect("There must be a game result, since the game is confirmed to be over.");
 
             // plays could be 0 or 1
             // 0 if the parent node was expanded, and sim'd on a different child
@@ -519,7 +980,11 @@ fn mcts_loop<TNode, TState>(
                 leaf.data().get_lock(),
                 || leaf.data().wins_plays().1 == 0,
                 || {
-                    backprop_sim_result(leaf, is_win);
+                    backprop_sim_result(leaf, outcome);
+
+                    if config.rave.is_some() {
+                        backprop_amaf(leaf, Vec::new(), outcome.reward);
+                    }
                 },
             );
 
@@ -530,13 +995,43 @@ fn mcts_loop<TNode, TState>(
                     // Update the terminating node so it knows its own end game result.
                     leaf.data().set_end_state_result(sim_result);
 
+                    // A terminal node's result is trivially proven.
+                    leaf.data().set_proven_result(sim_result);
+                    backprop_proven(leaf);
+
                     // TODO: these two guys can be combined
                     backprop_saturation(leaf);
-                    backprop_terminal_count(leaf, is_win);
+                    backprop_terminal_count(leaf, outcome.is_win);
                 },
             );
         }
     }
+
+    stats
+}
+
+/// Handles a leaf that was already expanded, but whose children were all filtered out
+/// of selection as saturated or proven, before the leaf itself was marked as such.
+/// If every child is proven, so is the leaf, which stops its parent from selecting it.
+/// Otherwise another thread is still backpropagating a child's saturation,
+/// so give it the chance to finish instead of busily selecting the same leaf.
+/// The leaf's play count is left alone, so it stays the sum of its children's.
+fn settle_filtered_leaf<TNode, TState>(leaf: &TNode)
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: GameState,
+{
+    let data = leaf.data();
+    let mover = data.state().current_player_turn();
+
+    match best_proven_result_for(leaf, mover) {
+        Some(result) => {
+            if data.set_proven_result(result) {
+                backprop_proven(leaf);
+            }
+        }
+        None => std::thread::yield_now(),
+    }
 }
 
 /// If the condition is true, acquires the lock, then confirms the condition is still true
@@ -557,6 +1052,38 @@ where
     }
 }
 
+/// Entry points into the search internals, for the benchmarks in benches/.
+/// Not part of the supported API, so only built with the bench-internals feature.
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub mod bench {
+    use super::*;
+
+    pub use super::mcts;
+
+    /// Expands the node, returning false if it was already expanded.
+    pub fn expand<TNode, TState>(node: &TNode) -> bool
+    where
+        TNode: Node<Data = MctsData<TState>>,
+        TState: MoveOrdering,
+    {
+        super::expand(node).is_ok()
+    }
+
+    /// Selects from the root down to a leaf, as a single-threaded search would.
+    pub fn select_to_leaf<TNode, TState>(
+        root: &TNode,
+        player_color: PlayerColor,
+        config: MctsConfig,
+    ) -> TNode::Handle
+    where
+        TNode: Node<Data = MctsData<TState>>,
+        TState: GameState,
+    {
+        super::select_to_leaf(root, player_color, 0.0, config).0
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -565,6 +1092,7 @@ pub mod tests {
     use lib_tic_tac_toe::tic_tac_toe_gamestate::{TicTacToeAction, TicTacToeState};
 
     use std::str::FromStr;
+    use std::sync::Arc;
 
     use monte_carlo_tree::arc_tree::ArcNode;
 
@@ -575,8 +1103,18 @@ pub mod tests {
         TicTacToeState::initial_state()
     }
 
+    /// A config that keeps searching proven nodes, so the search
+    /// only ends once the root is saturated (or the end condition is met).
+    fn exhaustive_config() -> MctsConfig {
+        MctsConfig {
+            end_condition: test_end_condition(),
+            solver: false,
+            ..Default::default()
+        }
+    }
+
     fn test_end_condition() -> MctsEndCondition {
-        MctsEndCondition::RolloutCount(1000)
+        MctsEndCondition::RolloutCount(5_000)
     }
 
     fn make_node<G>(data: MctsData<G>) -> impl Node<Data = MctsData<G>>
@@ -621,7 +1159,7 @@ pub mod tests {
         let tree_root = make_node(data.clone());
         let is_win = true;
 
-        backprop_sim_result(&tree_root, is_win);
+        backprop_sim_result(&tree_root, SimOutcome::from_win(is_win));
 
         let (wins, plays) = tree_root.data().wins_plays();
 
@@ -635,7 +1173,7 @@ pub mod tests {
         let tree_root = make_node(data.clone());
         let is_win = false;
 
-        backprop_sim_result(&tree_root, is_win);
+        backprop_sim_result(&tree_root, SimOutcome::from_win(is_win));
 
         let (wins, plays) = tree_root.data().wins_plays();
 
@@ -654,7 +1192,7 @@ pub mod tests {
         let child_level_4 = child_level_3.borrow().new_child(data.clone());
 
         let is_win = true;
-        backprop_sim_result(child_level_3.borrow(), is_win);
+        backprop_sim_result(child_level_3.borrow(), SimOutcome::from_win(is_win));
 
         assert_eq!(1, child_level_3.borrow().data().wins_plays().1);
         assert_eq!(1, child_level_2.borrow().data().wins_plays().1);
@@ -682,6 +1220,70 @@ pub mod tests {
         assert_eq!(9, children.len());
     }
 
+    #[test]
+    fn expand_expects_children_ordered_by_priority() {
+        let tree_root = ArcNode::new_root(make_test_data());
+
+        expand(&tree_root).unwrap();
+
+        let state = tree_root.data().state();
+        let priorities = tree_root
+            .children_read()
+            .iter()
+            .map(|c| state.move_priority(c.data().action().unwrap()))
+            .collect::<Vec<_>>();
+
+        // In TicTacToe, the center is the most promising position.
+        assert_eq!(
+            Some(TicTacToeAction::from_str("1,1").unwrap()),
+            tree_root.children_read()[0].data().action()
+        );
+        assert!(priorities.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn widening_child_limit_expects_grows_with_plays() {
+        let widening = WideningConfig {
+            coefficient: 2.0,
+            exponent: 0.5,
+        };
+
+        assert_eq!(1, widening.child_limit(0));
+        assert_eq!(2, widening.child_limit(1));
+        assert_eq!(20, widening.child_limit(100));
+    }
+
+    #[test]
+    fn select_child_with_widening_expects_only_first_children_considered() {
+        let tree_root = ArcNode::new_root(make_test_data());
+        expand(&tree_root).unwrap();
+
+        let children = tree_root.children_read().clone();
+
+        // Visit the first (most promising) child enough to make it look worse than
+        // the unvisited children.
+        for _ in 0..10 {
+            backprop_sim_result(&children[0], SimOutcome::from_win(false));
+        }
+
+        let config = MctsConfig {
+            widening: Some(WideningConfig {
+                coefficient: 1.0,
+                exponent: 0.0,
+            }),
+            ..Default::default()
+        };
+
+        // Only one child is ever introduced, so it must be selected.
+        let selected = select_child_for_traversal(&tree_root, PlayerColor::Black, 0.00, config);
+        assert!(Arc::ptr_eq(&children[0], &selected.unwrap()));
+
+        // Without widening, an unvisited child is preferred, in priority order.
+        let selected =
+            select_child_for_traversal(&tree_root, PlayerColor::Black, 0.00, Default::default());
+        assert!(Arc::ptr_eq(&children[1], &selected.unwrap()));
+    }
+
     #[test]
     fn expand_expects_adds_children_to_parent() {
         let tree_root = ArcNode::new_root(make_test_data());
@@ -753,11 +1355,11 @@ pub mod tests {
         child_level_4b.data().set_children_count(1);
 
         let is_win = true;
-        backprop_sim_result(child_level_3, is_win);
-        backprop_sim_result(child_level_4, is_win);
-        backprop_sim_result(child_level_4, is_win);
-        backprop_sim_result(child_level_4, is_win);
-        backprop_sim_result(child_level_4b, is_win);
+        backprop_sim_result(child_level_3, SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4, SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4, SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4, SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4b, SimOutcome::from_win(is_win));
 
         assert!(!child_level_3.data().is_saturated());
 
@@ -765,6 +1367,7 @@ pub mod tests {
             child_level_3_handle.borrow(),
             PlayerColor::Black,
             0.00,
+            MctsConfig::default(),
         )
         .expect("the child should have been selected.");
 
@@ -803,19 +1406,21 @@ pub mod tests {
         child_level_4b.borrow().data().set_children_count(2);
 
         let is_win = true;
-        backprop_sim_result(child_level_3.borrow(), is_win);
-        backprop_sim_result(child_level_4.borrow(), is_win);
-        backprop_sim_result(child_level_4.borrow(), is_win);
-        backprop_sim_result(child_level_4.borrow(), is_win);
-        backprop_sim_result(child_level_4.borrow(), is_win);
-        backprop_sim_result(child_level_4b.borrow(), is_win);
-        backprop_sim_result(child_level_4b.borrow(), is_win);
+        backprop_sim_result(child_level_3.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4b.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_level_4b.borrow(), SimOutcome::from_win(is_win));
 
-        let leaf = select_to_leaf(&tree_root, PlayerColor::Black, 0.00);
+        let (leaf, depth) =
+            select_to_leaf(&tree_root, PlayerColor::Black, 0.00, MctsConfig::default());
 
         let leaf = leaf.borrow();
 
         assert_eq!(2, leaf.data().wins_plays().1);
+        assert_eq!(4, depth);
     }
 
     #[test]
@@ -824,9 +1429,11 @@ pub mod tests {
 
         let tree_root = make_node(data.clone());
 
-        let leaf = select_to_leaf(&tree_root, PlayerColor::Black, 0.00);
+        let (leaf, depth) =
+            select_to_leaf(&tree_root, PlayerColor::Black, 0.00, MctsConfig::default());
         let leaf = leaf.borrow();
 
+        assert_eq!(0, depth);
         assert_eq!(10, leaf.data().wins_plays().1);
         assert_eq!(10, leaf.data().wins_plays().0);
     }
@@ -1007,7 +1614,7 @@ pub mod tests {
             PlayerColor::Black,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1054,24 +1661,25 @@ pub mod tests {
         // "visit" each child a different amount of times
         // child a: three visits
         let is_win = false;
-        backprop_sim_result(child_a.borrow(), is_win);
-        backprop_sim_result(child_a.borrow(), is_win);
-        backprop_sim_result(child_a.borrow(), is_win);
+        backprop_sim_result(child_a.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_a.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_a.borrow(), SimOutcome::from_win(is_win));
 
         // child b: two visits
-        backprop_sim_result(child_b.borrow(), is_win);
-        backprop_sim_result(child_b.borrow(), is_win);
+        backprop_sim_result(child_b.borrow(), SimOutcome::from_win(is_win));
+        backprop_sim_result(child_b.borrow(), SimOutcome::from_win(is_win));
 
         // child c: one visit
-        backprop_sim_result(child_c.borrow(), is_win);
+        backprop_sim_result(child_c.borrow(), SimOutcome::from_win(is_win));
 
         let parent_plays = tree_root.data().wins_plays().1;
 
         let unvisited_node_score =
-            score_node_for_traversal(child_d.borrow(), parent_plays, true, 0.00);
+            score_node_for_traversal(child_d.borrow(), parent_plays, true, 0.00, None);
 
         [child_a, child_b, child_c].iter().for_each(|c| {
-            let visited_node_score = score_node_for_traversal(c.borrow(), parent_plays, true, 0.00);
+            let visited_node_score =
+                score_node_for_traversal(c.borrow(), parent_plays, true, 0.00, None);
 
             assert!(
                 unvisited_node_score > visited_node_score,
@@ -1080,6 +1688,199 @@ pub mod tests {
         });
     }
 
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn normalize_margin_expects_range_zero_to_one() {
+        assert_eq!(1.0, normalize_margin(64, 64));
+        assert_eq!(0.0, normalize_margin(-64, 64));
+        assert_eq!(0.5, normalize_margin(0, 64));
+        assert_eq!(0.75, normalize_margin(16, 32));
+
+        // An empty score is treated as even.
+        assert_eq!(0.5, normalize_margin(0, 0));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn sim_outcome_expects_reward_follows_objective() {
+        let mut state = TicTacToeState::new();
+
+        // XXX
+        // OO_
+        // ___
+        let moves = vec!["0,2", "0,1", "1,2", "1,1", "2,2"]
+            .into_iter()
+            .map(|s| TicTacToeAction::from_str(s).unwrap());
+
+        state.apply_moves(moves);
+        assert!(state.is_game_over());
+
+        let win_loss =
+            SimOutcome::from_final_state(&state, PlayerColor::White, MctsObjective::WinLoss);
+        assert!(!win_loss.is_win);
+        assert_eq!(0.0, win_loss.reward);
+        assert_eq!(-1, win_loss.margin);
+
+        let margin =
+            SimOutcome::from_final_state(&state, PlayerColor::Black, MctsObjective::ScoreMargin);
+        assert!(margin.is_win);
+        assert_eq!(1.0, margin.reward);
+        assert_eq!(1, margin.margin);
+
+        let blend =
+            SimOutcome::from_final_state(&state, PlayerColor::White, MctsObjective::Blend(0.5));
+        assert_eq!(0.0, blend.reward);
+    }
+
+    #[test]
+    fn score_node_expects_prefers_higher_reward() {
+        let data = make_test_data();
+        let tree_root = make_node(data.clone());
+
+        let child_a = tree_root.new_child(data.clone());
+        let child_b = tree_root.new_child(data.clone());
+
+        let outcome = |reward| SimOutcome {
+            is_win: true,
+            reward,
+            margin: 0,
+        };
+
+        // Both children are wins every time, but child a wins by a larger margin.
+        backprop_sim_result(child_a.borrow(), outcome(0.9));
+        backprop_sim_result(child_a.borrow(), outcome(0.9));
+        backprop_sim_result(child_b.borrow(), outcome(0.6));
+        backprop_sim_result(child_b.borrow(), outcome(0.6));
+
+        let parent_plays = tree_root.data().wins_plays().1;
+
+        let a_score = score_node_for_traversal(child_a.borrow(), parent_plays, true, 0.00, None);
+        let b_score = score_node_for_traversal(child_b.borrow(), parent_plays, true, 0.00, None);
+
+        assert!(a_score > b_score);
+
+        // From the opponent's perspective, the smaller margin is preferred.
+        let a_score = score_node_for_traversal(child_a.borrow(), parent_plays, false, 0.00, None);
+        let b_score = score_node_for_traversal(child_b.borrow(), parent_plays, false, 0.00, None);
+
+        assert!(b_score > a_score);
+    }
+
+    #[test]
+    fn backprop_amaf_expects_credits_moves_played_later_by_same_player() {
+        let root_handle = ArcNode::new_root(make_test_data());
+        let root: &ArcNode<_> = root_handle.borrow();
+        expand(root).unwrap();
+
+        let action = |s| TicTacToeAction::from_str(s).unwrap();
+
+        let sim_node = root
+            .children_read()
+            .iter()
+            .find(|c| c.data().action() == Some(action("1,1")))
+            .cloned()
+            .unwrap();
+
+        // After Black plays 1,1, the simulation continues with White 0,0 and Black 2,2.
+        let moves = vec![
+            (PlayerColor::White, action("0,0")),
+            (PlayerColor::Black, action("2,2")),
+        ];
+
+        backprop_amaf(&sim_node, moves, 1.0);
+
+        for child in root.children_read().iter() {
+            let expected_plays = match child.data().action() {
+                // The move actually taken, and the move Black played later.
+                Some(a) if a == action("1,1") || a == action("2,2") => 1,
+                // Only White played 0,0, so it says nothing about Black playing there.
+                _ => 0,
+            };
+
+            assert_eq!(expected_plays, child.data().amaf_reward_plays().1);
+        }
+    }
+
+    #[test]
+    fn score_node_with_rave_expects_prefers_higher_amaf_reward() {
+        let data = make_test_data();
+        let tree_root = make_node(data.clone());
+
+        let child_a = tree_root.new_child(data.clone());
+        let child_b = tree_root.new_child(data.clone());
+
+        // Identical direct statistics...
+        backprop_sim_result(child_a.borrow(), SimOutcome::from_win(true));
+        backprop_sim_result(child_b.borrow(), SimOutcome::from_win(true));
+
+        let parent_plays = tree_root.data().wins_plays().1;
+
+        let a_plain = score_node_for_traversal(child_a.borrow(), parent_plays, true, 0.00, None);
+        let b_plain = score_node_for_traversal(child_b.borrow(), parent_plays, true, 0.00, None);
+        assert!((a_plain - b_plain).abs() < f32::EPSILON);
+
+        // ...but child a looks much better when played at any point.
+        for _ in 0..10 {
+            child_a.borrow().data().add_amaf_play(1.0);
+            child_b.borrow().data().add_amaf_play(0.0);
+        }
+
+        let rave = Some(RaveConfig::default());
+
+        let a_score = score_node_for_traversal(child_a.borrow(), parent_plays, true, 0.00, rave);
+        let b_score = score_node_for_traversal(child_b.borrow(), parent_plays, true, 0.00, rave);
+        assert!(a_score > b_score);
+
+        // From the opponent's perspective, the lower AMAF reward is preferred.
+        let a_score = score_node_for_traversal(child_a.borrow(), parent_plays, false, 0.00, rave);
+        let b_score = score_node_for_traversal(child_b.borrow(), parent_plays, false, 0.00, rave);
+        assert!(b_score > a_score);
+    }
+
+    #[test]
+    fn rave_beta_expects_decays_with_plays() {
+        let rave = RaveConfig { equivalence: 100.0 };
+
+        assert!((rave.beta(0.0) - 1.0).abs() < f32::EPSILON);
+        assert!(rave.beta(10.0) > rave.beta(1_000.0));
+        assert!(rave.beta(1_000_000.0) < 0.01);
+    }
+
+    #[test]
+    fn mcts_when_deterministic_expects_identical_trees() {
+        let run = || {
+            let root = ArcNode::new_root(make_test_data());
+            let (results, _) = mcts::<ArcNode<_>, _>(
+                root.clone(),
+                PlayerColor::Black,
+                MctsConfig::deterministic(99, 500),
+            );
+
+            (format!("{:?}", results), root.data().tree_size())
+        };
+
+        let (first_results, first_size) = run();
+        let (second_results, second_size) = run();
+
+        assert_eq!(first_results, second_results);
+        assert_eq!(first_size, second_size);
+    }
+
+    #[test]
+    fn mcts_expects_counts_rollouts_and_depth() {
+        let root = ArcNode::new_root(make_test_data());
+        let (_, stats) = mcts::<ArcNode<_>, _>(
+            root.clone(),
+            PlayerColor::Black,
+            MctsConfig::deterministic(5, 200),
+        );
+
+        assert_eq!(1, stats.thread_count);
+        assert!(stats.rollouts > 0 && stats.rollouts <= 200);
+        assert!(stats.max_depth >= 1);
+        assert!(stats.average_depth() <= stats.max_depth as f32);
+    }
+
     #[test]
     fn simulate_runs_to_completion_and_terminates() {
         let mut initial_state = make_test_state();
@@ -1088,7 +1889,7 @@ pub mod tests {
 
         let tree_root = make_node(data.clone());
 
-        let _sim_result = simulate(&tree_root, &mut crate::util::get_rng_deterministic());
+        let _sim_result = simulate(&tree_root, &mut crate::util::get_rng_deterministic(), None);
     }
 
     #[test]
@@ -1117,7 +1918,7 @@ pub mod tests {
             PlayerColor::Black,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1157,7 +1958,7 @@ pub mod tests {
             PlayerColor::Black,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1187,7 +1988,7 @@ pub mod tests {
             PlayerColor::Black,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1241,7 +2042,7 @@ pub mod tests {
             PlayerColor::White,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1288,7 +2089,7 @@ pub mod tests {
             PlayerColor::White,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         assert!(
@@ -1365,7 +2166,7 @@ pub mod tests {
             PlayerColor::Black,
             TEST_THREAD_COUNT,
             TEST_JITTER,
-            test_end_condition(),
+            exhaustive_config(),
         );
 
         let root_terminal_count_after = root.data().terminal_count();
@@ -1375,4 +2176,132 @@ pub mod tests {
             "By adding one new saturated node, expects root to get its terminal count incremented by one."
         );
     }
+
+    fn make_forced_win_state() -> TicTacToeState {
+        let mut state = TicTacToeState::initial_state();
+
+        // O_X
+        // _O_
+        // X__
+        let moves = vec!["0,0", "1,1", "2,2", "0,2"]
+            .into_iter()
+            .map(|s| TicTacToeAction::from_str(s).unwrap());
+
+        state.apply_moves(moves);
+
+        state
+    }
+
+    #[test]
+    fn backprop_proven_expects_parent_proven_when_child_wins_for_mover() {
+        let tree_root = ArcNode::new_root(MctsData::new(make_forced_win_state(), 0, 0, None));
+        expand(&tree_root).unwrap();
+
+        let children = tree_root.children_read().clone();
+
+        // Black is the mover at the root, so a single proven win for Black proves the root.
+        children[0].data().set_proven_result(GameResult::BlackWins);
+        backprop_proven(&children[0]);
+
+        assert_eq!(
+            Some(GameResult::BlackWins),
+            tree_root.data().proven_result()
+        );
+    }
+
+    #[test]
+    fn backprop_proven_expects_parent_unproven_until_all_children_lose() {
+        let tree_root = ArcNode::new_root(MctsData::new(make_forced_win_state(), 0, 0, None));
+        expand(&tree_root).unwrap();
+
+        let children = tree_root.children_read().clone();
+        let (last, rest) = children.split_last().unwrap();
+
+        for child in rest {
+            child.data().set_proven_result(GameResult::WhiteWins);
+            backprop_proven(child);
+
+            assert!(
+                !tree_root.data().is_proven(),
+                "A loss for the mover proves nothing while other children are unproven."
+            );
+        }
+
+        last.data().set_proven_result(GameResult::Tie);
+        backprop_proven(last);
+
+        assert_eq!(
+            Some(GameResult::Tie),
+            tree_root.data().proven_result(),
+            "Once every child is proven, the parent takes the best result for its mover."
+        );
+    }
+
+    #[test]
+    fn mcts_solver_expects_proves_forced_win_before_saturation() {
+        let tree_root = ArcNode::new_root(MctsData::new(make_forced_win_state(), 0, 0, None));
+
+        mcts_executor(
+            &tree_root,
+            PlayerColor::Black,
+            1,
+            0.00,
+            MctsConfig {
+                end_condition: test_end_condition(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            Some(GameResult::BlackWins),
+            tree_root.data().proven_result(),
+            "Black can force a win by playing 2,0, so the root must be proven."
+        );
+
+        assert!(
+            !tree_root.data().is_saturated(),
+            "The solver should stop as soon as the root is proven, before exploring everything."
+        );
+
+        let winning_action = TicTacToeAction::from_str("2,0").unwrap();
+
+        for child in tree_root.children_read().iter() {
+            let data = child.data();
+
+            if data.action().unwrap() == winning_action {
+                assert_eq!(Some(GameResult::BlackWins), data.proven_result());
+            } else {
+                assert_ne!(Some(GameResult::BlackWins), data.proven_result());
+            }
+        }
+    }
+
+    #[test]
+    fn mcts_loop_when_every_child_proven_expects_proves_leaf() {
+        let tree_root = ArcNode::new_root(make_test_data());
+        expand(&tree_root).unwrap();
+
+        // The children are proven, but (as if another thread were still backpropagating)
+        // the root isn't, so selection filters out every child and returns the root itself.
+        for child in tree_root.children_read().iter() {
+            child.data().set_proven_result(GameResult::Tie);
+        }
+
+        let stats = mcts_loop(
+            &tree_root,
+            PlayerColor::Black,
+            0.00,
+            MctsConfig {
+                end_condition: MctsEndCondition::RolloutCount(10),
+                ..Default::default()
+            },
+            util::get_rng(Some(0)),
+        );
+
+        assert_eq!(Some(GameResult::Tie), tree_root.data().proven_result());
+
+        // The search ends as soon as the root is proven, without simulating anything.
+        assert_eq!(0, stats.rollouts);
+        assert_eq!(0, tree_root.data().wins_plays().1);
+    }
 }
@@ -1,13 +1,33 @@
-use crate::util::random_choice;
+use crate::util::{get_rng, random_choice};
 use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use rand::rngs::StdRng;
+use std::cell::RefCell;
 
 pub struct RandomAgent {
     player_color: PlayerColor,
+    rng: RefCell<StdRng>,
+}
+
+impl RandomAgent {
+    pub fn new(player_color: PlayerColor) -> Self {
+        Self {
+            player_color,
+            rng: RefCell::new(get_rng(None)),
+        }
+    }
+
+    /// Creates an agent whose sequence of moves is fully determined by the given seed.
+    pub fn with_seed(player_color: PlayerColor, seed: u64) -> Self {
+        Self {
+            player_color,
+            rng: RefCell::new(get_rng(Some(seed))),
+        }
+    }
 }
 
 impl<TState: GameState> GameAgent<TState> for RandomAgent {
     fn pick_move(&self, _state: &TState, legal_moves: &[TState::Action]) -> TState::Action {
-        random_choice(&legal_moves, &mut crate::util::get_rng())
+        random_choice(legal_moves, &mut *self.rng.borrow_mut())
     }
 
     fn player_color(&self) -> PlayerColor {
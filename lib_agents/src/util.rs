@@ -1,6 +1,6 @@
-use rand::rngs::SmallRng;
+use rand::rngs::{SmallRng, StdRng};
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{FromEntropy, Rng, SeedableRng};
 
 pub fn random_pick<'a, T, R>(choices: &'a [T], rng: &mut R) -> Option<&'a T>
 where
@@ -17,14 +17,56 @@ where
     *random_pick(choices, rng).expect("Attempted to pick a random choice, but failed")
 }
 
-pub(crate) fn get_rng() -> impl rand::Rng + Clone {
-    // use rand::FromEntropy;
-    // SmallRng::from_entropy()
-    rand::thread_rng()
+/// Returns an rng seeded with the given seed, so it always produces the same sequence,
+/// or an rng seeded from entropy if no seed is given.
+pub(crate) fn get_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
 }
 
 #[allow(unused)]
 pub(crate) fn get_rng_deterministic() -> impl rand::Rng {
-    use rand::SeedableRng;
     SmallRng::from_seed([0; 16])
 }
+
+/// Derives an independent seed for the given stream (e.x. a thread index) from a parent seed,
+/// so that related streams don't produce correlated sequences.
+pub(crate) fn derive_seed(seed: u64, stream: u64) -> u64 {
+    // The SplitMix64 finalizer.
+    let mut z = seed ^ stream.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_rng_same_seed_expects_same_sequence() {
+        let a: Vec<u32> = get_rng(Some(7))
+            .sample_iter(&rand::distributions::Standard)
+            .take(8)
+            .collect();
+        let b: Vec<u32> = get_rng(Some(7))
+            .sample_iter(&rand::distributions::Standard)
+            .take(8)
+            .collect();
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn derive_seed_expects_distinct_streams() {
+        let seeds = (0..4).map(|i| derive_seed(42, i)).collect::<Vec<_>>();
+
+        assert_ne!(seeds[0], seeds[1]);
+        assert_ne!(seeds[1], seeds[2]);
+        assert_ne!(seeds[2], seeds[3]);
+        assert_eq!(seeds[0], derive_seed(42, 0));
+    }
+}
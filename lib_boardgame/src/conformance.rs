@@ -0,0 +1,272 @@
+//! A conformance kit for GameState implementations: plays random games and checks
+//! the invariants every game must uphold at each step, so a game's own tests can
+//! validate it with a single call (see assert_conformance()).
+//!
+//! The invariants checked are:
+//! - every game ends within the configured number of moves;
+//! - legal_moves() is empty exactly when the game is over;
+//! - game_result() is Some exactly when the game is over;
+//! - next_state() leaves the original state unchanged;
+//! - a clone is deep: moves applied to it don't affect the original,
+//!   and both reach the same state when given the same move;
+//! - a forced pass is the only legal move, and hands the turn to the opponent.
+//!
+//! Games that implement Mobility can also be checked with assert_mobility_conformance(),
+//! which additionally checks that:
+//! - legal_moves_for() the player to move agrees with legal_moves();
+//! - legal_moves_for() either player is empty once the game is over.
+
+use crate::{GameAction, GameState, Mobility, PlayerColor};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::fmt;
+
+/// Options for a conformance check.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ConformanceConfig {
+    /// The number of random games to play.
+    pub games: usize,
+
+    /// The seed for the random moves, so failures can be reproduced.
+    pub seed: u64,
+
+    /// A game that hasn't ended after this many moves fails the check.
+    pub max_moves: usize,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> Self {
+        Self {
+            games: 100,
+            seed: 0,
+            max_moves: 1000,
+        }
+    }
+}
+
+/// A broken invariant, with the game and moves that led to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConformanceFailure {
+    /// Which of the random games failed, counting from 0.
+    pub game: usize,
+
+    /// The moves played from the start of the game, as displayed by the game.
+    pub moves: Vec<String>,
+
+    /// A description of the broken invariant.
+    pub message: String,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Game {} failed after moves [{}]: {}",
+            self.game,
+            self.moves.join(", "),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConformanceFailure {}
+
+/// Everything observable about a state through GameState,
+/// used to compare states without requiring PartialEq.
+fn fingerprint<T: GameState>(state: &T) -> String {
+    use crate::PlayerColor::{Black, White};
+
+    format!(
+        "{}|{:?}|{:?}|{}|{:?}|{}/{}",
+        state,
+        state.current_player_turn(),
+        state.legal_moves(),
+        state.is_game_over(),
+        state.game_result(),
+        state.player_score(Black),
+        state.player_score(White),
+    )
+}
+
+/// Checks the invariants of a single state, and of applying the given action to it.
+fn check_step<T: GameState>(state: &T, action: Option<T::Action>) -> Result<(), String> {
+    let legal_moves = state.legal_moves();
+
+    if state.is_game_over() != legal_moves.is_empty() {
+        return Err(format!(
+            "is_game_over() is {}, but there are {} legal moves",
+            state.is_game_over(),
+            legal_moves.len()
+        ));
+    }
+
+    if state.is_game_over() != state.game_result().is_some() {
+        return Err(format!(
+            "is_game_over() is {}, but game_result() is {:?}",
+            state.is_game_over(),
+            state.game_result()
+        ));
+    }
+
+    let action = match action {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+
+    if action.is_forced_pass() && legal_moves.len() != 1 {
+        return Err(format!(
+            "a forced pass was legal alongside {} other moves",
+            legal_moves.len() - 1
+        ));
+    }
+
+    let before = fingerprint(state);
+
+    let next = state.next_state(action);
+    if fingerprint(state) != before {
+        return Err(format!("next_state({}) changed the original state", action));
+    }
+
+    if action.is_forced_pass() && next.current_player_turn() == state.current_player_turn() {
+        return Err("a forced pass didn't hand the turn to the opponent".to_owned());
+    }
+
+    let mut clone = state.clone();
+    clone.apply_move(action);
+    if fingerprint(state) != before {
+        return Err(format!(
+            "applying {} to a clone changed the original state",
+            action
+        ));
+    }
+
+    if fingerprint(&clone) != fingerprint(&next) {
+        return Err(format!(
+            "applying {} to a clone differs from next_state({})",
+            action, action
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that Mobility agrees with GameState about the given state.
+fn check_mobility<T: Mobility>(state: &T) -> Result<(), String> {
+    let player = state.current_player_turn();
+
+    if state.legal_moves_for(player).as_slice() != state.legal_moves() {
+        return Err(format!(
+            "legal_moves_for({:?}) is {:?}, but legal_moves() is {:?}",
+            player,
+            state.legal_moves_for(player),
+            state.legal_moves()
+        ));
+    }
+
+    if state.is_game_over() {
+        for player in [PlayerColor::Black, PlayerColor::White] {
+            let moves = state.legal_moves_for(player);
+
+            if !moves.is_empty() {
+                return Err(format!(
+                    "the game is over, but legal_moves_for({:?}) is {:?}",
+                    player, moves
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays random games from the given state, checking the invariants at every step,
+/// along with any extra check of each state. Returns the first broken invariant found.
+fn play_random_games<T, F>(
+    start: &T,
+    config: ConformanceConfig,
+    check_state: F,
+) -> Result<(), ConformanceFailure>
+where
+    T: GameState,
+    F: Fn(&T) -> Result<(), String>,
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    for game in 0..config.games {
+        let mut state = start.clone();
+        let mut moves = Vec::new();
+
+        let fail = |moves: &[String], message: String| ConformanceFailure {
+            game,
+            moves: moves.to_vec(),
+            message,
+        };
+
+        loop {
+            let action = state.legal_moves().choose(&mut rng).copied();
+
+            check_step(&state, action)
+                .and_then(|_| check_state(&state))
+                .map_err(|message| fail(&moves, message))?;
+
+            let action = match action {
+                Some(action) => action,
+                None => break,
+            };
+
+            if moves.len() >= config.max_moves {
+                return Err(fail(
+                    &moves,
+                    format!("the game didn't end within {} moves", config.max_moves),
+                ));
+            }
+
+            state.apply_move(action);
+            moves.push(action.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays random games from the given state, checking the invariants at every step.
+/// Returns the first broken invariant found.
+pub fn check_conformance_from<T: GameState>(
+    start: &T,
+    config: ConformanceConfig,
+) -> Result<(), ConformanceFailure> {
+    play_random_games(start, config, |_| Ok(()))
+}
+
+/// Like check_conformance_from(), but also checks the Mobility invariants.
+pub fn check_mobility_conformance_from<T: Mobility>(
+    start: &T,
+    config: ConformanceConfig,
+) -> Result<(), ConformanceFailure> {
+    play_random_games(start, config, check_mobility)
+}
+
+/// Like check_conformance_from(), starting from the game's initial state.
+pub fn check_conformance<T: GameState>(
+    config: ConformanceConfig,
+) -> Result<(), ConformanceFailure> {
+    check_conformance_from(&T::initial_state(), config)
+}
+
+/// Checks the game's conformance with the default config, from its initial state,
+/// panicking with a description of the first broken invariant.
+pub fn assert_conformance<T: GameState>() {
+    if let Err(failure) = check_conformance::<T>(ConformanceConfig::default()) {
+        panic!("{}", failure);
+    }
+}
+
+/// Like assert_conformance(), but also checks the Mobility invariants.
+pub fn assert_mobility_conformance<T: Mobility>() {
+    let start = T::initial_state();
+
+    if let Err(failure) = check_mobility_conformance_from(&start, ConformanceConfig::default()) {
+        panic!("{}", failure);
+    }
+}
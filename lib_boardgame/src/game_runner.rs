@@ -1,4 +1,7 @@
-use crate::{GameAction, GameAgent, GameResult, GameState, PlayerColor};
+use crate::{
+    GameAction, GameAgent, GameResult, GameState, IllegalMoveError, IllegalMoveReason, PlayerColor,
+    TurnChoice, UndoMove,
+};
 use lib_printer::{out, out_impl};
 
 /// A trait that describes a game runner.
@@ -10,39 +13,117 @@ pub trait GameRunner<T: GameState> {
 /// Probably all you need to run most games.
 pub struct GeneralGameRunner;
 
-fn player_take_turn<S>(game_state: &mut S, agent: &dyn GameAgent<S>) -> S::Action
+/// How many illegal moves an agent may attempt in a single turn before forfeiting the game.
+const MAX_ILLEGAL_MOVE_ATTEMPTS: usize = 3;
+
+/// Asks the agent for its move and applies it to the state,
+/// asking again if the agent picks an illegal move.
+/// Returns the last error if the agent never picks a legal move.
+fn player_take_turn<S>(
+    game_state: &mut S,
+    agent: &dyn GameAgent<S>,
+) -> Result<S::Action, IllegalMoveError>
 where
     S: GameState,
 {
     let player_color = agent.player_color();
-    let legal_moves = game_state.legal_moves(player_color);
-
-    let selected_action = agent.pick_move(game_state, legal_moves);
-
-    if legal_moves
-        .iter()
-        .find(|&&m| m == selected_action)
-        .is_none()
-    {
-        panic!("Agent provided a move that is illegal.");
-    }
-
-    if legal_moves.len() == 1 && legal_moves[0].is_forced_pass() {
-        out!(
-            "Player {:?} has no options, so they pass their turn.",
-            player_color
-        );
+    let mut last_error = None;
+
+    for _ in 0..MAX_ILLEGAL_MOVE_ATTEMPTS {
+        let legal_moves = game_state.legal_moves();
+        let is_forced_pass = legal_moves.len() == 1 && legal_moves[0].is_forced_pass();
+
+        let selected_action = agent.pick_move(game_state, legal_moves);
+
+        match game_state.try_apply_move(selected_action) {
+            Ok(()) => {
+                if is_forced_pass {
+                    out!(
+                        "Player {:?} has no options, so they pass their turn.",
+                        player_color
+                    );
+                }
+
+                return Ok(selected_action);
+            }
+            Err(e) => {
+                out!("Player {:?} attempted an illegal move. {}", player_color, e);
+                last_error = Some(e);
+            }
+        }
     }
 
-    selected_action
+    Err(last_error.expect("At least one move must have been attempted."))
 }
 
 impl<T> GameRunner<T> for GeneralGameRunner
 where
     T: GameState,
 {
+    /// Plays the game to its end, from the game's initial state.
+    /// An agent that keeps picking illegal moves forfeits the game to its opponent.
     fn play_to_end(black_agent: &dyn GameAgent<T>, white_agent: &dyn GameAgent<T>) -> GameResult {
+        GeneralGameRunner::play_from(T::initial_state(), black_agent, white_agent)
+    }
+}
+
+impl GeneralGameRunner {
+    /// Plays the game to its end, from the given state
+    /// (e.g. a custom starting position, or a rule variant's initial state).
+    /// An agent that keeps picking illegal moves forfeits the game to its opponent.
+    pub fn play_from<T: GameState>(
+        mut game_state: T,
+        black_agent: &dyn GameAgent<T>,
+        white_agent: &dyn GameAgent<T>,
+    ) -> GameResult {
+        while !game_state.is_game_over() {
+            out!("{}", game_state.human_friendly());
+            let cur_player_color = game_state.current_player_turn();
+
+            let agent_to_play = match cur_player_color {
+                PlayerColor::Black => black_agent,
+                PlayerColor::White => white_agent,
+            };
+
+            let selected_action = match player_take_turn(&mut game_state, agent_to_play) {
+                Ok(action) => action,
+                Err(e) => {
+                    out!("Player {:?} forfeits the game. {}", cur_player_color, e);
+
+                    return forfeit(cur_player_color);
+                }
+            };
+
+            out!(
+                "Player {:?} picked move {:?}",
+                cur_player_color,
+                selected_action
+            );
+
+            black_agent.observe_action(cur_player_color, selected_action, &game_state);
+            white_agent.observe_action(cur_player_color, selected_action, &game_state);
+        }
+
+        out!("{}", game_state.human_friendly());
+
+        game_state
+            .game_result()
+            .expect("The game is over, so there must be a game result.")
+    }
+
+    /// Plays the game to its end, like play_to_end(),
+    /// but lets agents take back their previous move (see GameAgent::pick_turn()).
+    /// Taking back a move also undoes every move made after it.
+    pub fn play_to_end_with_take_backs<T>(
+        black_agent: &dyn GameAgent<T>,
+        white_agent: &dyn GameAgent<T>,
+    ) -> GameResult
+    where
+        T: UndoMove,
+    {
         let mut game_state = T::initial_state();
+        let mut history: Vec<(PlayerColor, T::Action, T::Undo)> = Vec::new();
+        let mut illegal_attempts = 0;
 
         while !game_state.is_game_over() {
             out!("{}", game_state.human_friendly());
@@ -53,7 +134,56 @@ where
                 PlayerColor::White => white_agent,
             };
 
-            let selected_action = player_take_turn(&mut game_state, agent_to_play);
+            let legal_moves = game_state.legal_moves();
+
+            let selected_action = match agent_to_play.pick_turn(&game_state, legal_moves) {
+                TurnChoice::Play(action) => action,
+                TurnChoice::TakeBack => {
+                    if !history
+                        .iter()
+                        .any(|&(player, _, _)| player == cur_player_color)
+                    {
+                        out!("Player {:?} has no move to take back.", cur_player_color);
+                        continue;
+                    }
+
+                    // Undo every move back to (and including) the player's previous move.
+                    while let Some((player, action, undo)) = history.pop() {
+                        game_state.undo_move(undo);
+
+                        black_agent.observe_undo(player, action, &game_state);
+                        white_agent.observe_undo(player, action, &game_state);
+
+                        if player == cur_player_color {
+                            break;
+                        }
+                    }
+
+                    out!("Player {:?} took back their move.", cur_player_color);
+                    continue;
+                }
+            };
+
+            if !legal_moves.contains(&selected_action) {
+                let error = IllegalMoveError::new(selected_action, IllegalMoveReason::NotLegal);
+                out!(
+                    "Player {:?} attempted an illegal move. {}",
+                    cur_player_color,
+                    error
+                );
+
+                illegal_attempts += 1;
+
+                if illegal_attempts >= MAX_ILLEGAL_MOVE_ATTEMPTS {
+                    out!("Player {:?} forfeits the game.", cur_player_color);
+
+                    return forfeit(cur_player_color);
+                }
+
+                continue;
+            }
+
+            illegal_attempts = 0;
 
             out!(
                 "Player {:?} picked move {:?}",
@@ -61,7 +191,8 @@ where
                 selected_action
             );
 
-            game_state.apply_move(selected_action);
+            let undo = game_state.make_move(selected_action);
+            history.push((cur_player_color, selected_action, undo));
 
             black_agent.observe_action(cur_player_color, selected_action, &game_state);
             white_agent.observe_action(cur_player_color, selected_action, &game_state);
@@ -74,3 +205,11 @@ where
             .expect("The game is over, so there must be a game result.")
     }
 }
+
+/// The result of the given player forfeiting the game.
+fn forfeit(player: PlayerColor) -> GameResult {
+    match player {
+        PlayerColor::Black => GameResult::WhiteWins,
+        PlayerColor::White => GameResult::BlackWins,
+    }
+}
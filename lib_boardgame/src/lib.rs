@@ -1,9 +1,14 @@
+#[cfg(feature = "conformance")]
+pub mod conformance;
 mod game_runner;
+mod perft;
+mod solver;
 
-use lib_printer::{out, out_impl};
-use std::fmt::{Debug, Display};
+use std::fmt::{self, Debug, Display};
 
 pub use game_runner::{GameRunner, GeneralGameRunner};
+pub use perft::{perft, perft_divide};
+pub use solver::{Mistake, Oracle};
 
 /// An enum representing the two possible player colors for all games.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -20,6 +25,23 @@ impl PlayerColor {
             PlayerColor::White => PlayerColor::Black,
         }
     }
+
+    /// Encodes the color as a single byte, for use in a StateEncoding.
+    pub fn to_byte(self) -> u8 {
+        match self {
+            PlayerColor::Black => 0,
+            PlayerColor::White => 1,
+        }
+    }
+
+    /// Decodes a color encoded by to_byte(), or None if the byte is not a valid encoding.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(PlayerColor::Black),
+            1 => Some(PlayerColor::White),
+            _ => None,
+        }
+    }
 }
 
 /// An enum representing the possible
@@ -42,11 +64,89 @@ impl GameResult {
     }
 }
 
+/// The reason a move could not be applied to a state.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IllegalMoveReason {
+    /// The move refers to a position outside of the board.
+    OutOfBounds,
+
+    /// The move's position (or column) is already filled.
+    Occupied,
+
+    /// The move is not among the legal moves of the player to move.
+    NotLegal,
+
+    /// The game is over, so no move may be applied.
+    GameOver,
+}
+
+impl Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            IllegalMoveReason::OutOfBounds => "the position is out of bounds",
+            IllegalMoveReason::Occupied => "the position is already occupied",
+            IllegalMoveReason::NotLegal => "it is not a legal move",
+            IllegalMoveReason::GameOver => "the game is over",
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+/// An error describing a move that could not be applied to a state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IllegalMoveError {
+    /// The move, as displayed by the game.
+    pub action: String,
+    pub reason: IllegalMoveReason,
+}
+
+impl IllegalMoveError {
+    pub fn new(action: impl Display, reason: IllegalMoveReason) -> Self {
+        Self {
+            action: action.to_string(),
+            reason,
+        }
+    }
+}
+
+impl Display for IllegalMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Illegal move {}: {}", self.action, self.reason)
+    }
+}
+
+impl std::error::Error for IllegalMoveError {}
+
+/// An error describing why a position could not be set up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PositionError {
+    /// The text could not be parsed as a position.
+    Malformed(String),
+
+    /// The position is well-formed, but could never arise in a game.
+    Impossible(String),
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::Malformed(msg) => write!(f, "Malformed position: {}", msg),
+            PositionError::Impossible(msg) => write!(f, "Impossible position: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
 /// Describes a move a player can make in a game.
 /// I.e., in Reversi, a move could be at position (3,7).
 pub trait GameAction: Copy + Debug + PartialEq + Display {
     /// Returns true if this GameMove represents a forced turn pass.
     fn is_forced_pass(self) -> bool;
+
+    /// Describes how a player types this kind of action, for input prompts, e.g. "x,y".
+    fn input_hint() -> String;
 }
 
 /// A trait describing a complete state of some Game,
@@ -70,13 +170,31 @@ pub trait GameState: Clone + Display {
     /// Implementors probably want to use initialize_board() to achieve this.
     fn initial_state() -> Self;
 
-    /// Returns the possible moves the given player can make for the current state.
-    fn legal_moves(&self, player: PlayerColor) -> &[Self::Action];
+    /// Returns the possible moves the player to move can make in the current state.
+    fn legal_moves(&self) -> &[Self::Action];
 
     /// Apply the given move (or 'action') to this state, mutating this state
     /// and advancing it to the resulting state.
+    /// The move must be legal; see try_apply_move() for moves that may not be.
     fn apply_move(&mut self, action: Self::Action);
 
+    /// Applies the given move like apply_move(), but if the move is illegal,
+    /// leaves this state unchanged and returns an error describing why.
+    /// The default implementation checks the move against legal_moves().
+    fn try_apply_move(&mut self, action: Self::Action) -> Result<(), IllegalMoveError> {
+        if self.is_game_over() {
+            return Err(IllegalMoveError::new(action, IllegalMoveReason::GameOver));
+        }
+
+        if !self.legal_moves().contains(&action) {
+            return Err(IllegalMoveError::new(action, IllegalMoveReason::NotLegal));
+        }
+
+        self.apply_move(action);
+
+        Ok(())
+    }
+
     /// Returns the player color whose turn it currently is.
     fn current_player_turn(&self) -> PlayerColor;
 
@@ -133,6 +251,110 @@ pub trait GameState: Clone + Display {
     }
 }
 
+/// A heuristic ordering over the legal moves of a GameState.
+/// Search agents use it to decide which moves deserve attention first,
+/// so each game can supply its own domain knowledge.
+pub trait MoveOrdering: GameState {
+    /// A heuristic priority for the given legal action in this state,
+    /// where a higher priority means the action is more promising.
+    /// The default implementation gives every action the same priority,
+    /// preserving the order of legal_moves().
+    fn move_priority(&self, _action: Self::Action) -> f32 {
+        0.0
+    }
+}
+
+/// The legal moves of either player, for games where the moves of the player
+/// not on turn are cheap to compute (e.g. to measure the opponent's mobility).
+pub trait Mobility: GameState {
+    /// Returns the possible moves the given player could make in the current state,
+    /// if it were their turn. Like legal_moves(), this is empty once the game is over.
+    fn legal_moves_for(&self, player: PlayerColor) -> Vec<Self::Action>;
+}
+
+/// A heuristic estimate of a state's value, for searches that stop before the game ends
+/// (e.g. alpha-beta at its depth limit, or truncated MCTS simulations).
+pub trait StaticEvaluation: GameState {
+    /// The estimated value of this state for the given player, where higher is better.
+    /// Values are only comparable between states of the same game.
+    fn evaluate(&self, player: PlayerColor) -> f32;
+}
+
+/// Applying and undoing moves in place, so search can make and unmake moves
+/// without cloning the entire state for every child.
+pub trait UndoMove: GameState {
+    /// The record of an applied move, holding what undo_move() needs to restore the prior state.
+    type Undo;
+
+    /// Applies the given legal move like apply_move(), returning its undo record.
+    fn make_move(&mut self, action: Self::Action) -> Self::Undo;
+
+    /// Restores the state from before the move that produced the given undo record.
+    /// Moves must be undone in the reverse of the order they were made.
+    fn undo_move(&mut self, undo: Self::Undo);
+}
+
+/// Setting up arbitrary positions, and a textual format for them.
+pub trait PositionSetup: GameState {
+    /// The pieces on the board, in the game's own layout.
+    type Board;
+
+    /// Creates a state with the given pieces and player to move,
+    /// or returns an error if the position could never arise in a game.
+    fn from_position(board: Self::Board, side_to_move: PlayerColor) -> Result<Self, PositionError>;
+
+    /// Parses a position in the game's textual format, as written by position_string().
+    fn parse_position(text: &str) -> Result<Self, PositionError>;
+
+    /// This position in the game's textual format:
+    /// one character per square, followed by the player to move.
+    fn position_string(&self) -> String;
+}
+
+/// Splits a textual position into its square characters and the character
+/// naming the player to move, ignoring whitespace.
+/// Returns an error unless there is exactly one character per square, plus the player.
+pub fn split_position_text(
+    text: &str,
+    square_count: usize,
+) -> Result<(Vec<char>, PlayerColor), PositionError> {
+    let mut chars = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+
+    if chars.len() != square_count + 1 {
+        return Err(PositionError::Malformed(format!(
+            "expected {} squares and the player to move, but found {} characters",
+            square_count,
+            chars.len()
+        )));
+    }
+
+    let side_to_move = match chars.pop() {
+        Some('*') | Some('X') | Some('x') | Some('B') | Some('b') => PlayerColor::Black,
+        Some('O') | Some('o') | Some('W') | Some('w') => PlayerColor::White,
+        c => {
+            return Err(PositionError::Malformed(format!(
+                "unrecognized player to move: {:?}",
+                c
+            )))
+        }
+    };
+
+    Ok((chars, side_to_move))
+}
+
+/// A compact binary encoding of a GameState, so states can be persisted and reloaded.
+pub trait StateEncoding: GameState {
+    /// Encodes this state as bytes.
+    fn encode_state(&self) -> Vec<u8>;
+
+    /// Decodes a state previously encoded by encode_state(),
+    /// or returns None if the bytes are not a valid encoding.
+    fn decode_state(bytes: &[u8]) -> Option<Self>;
+}
+
 /// A trait representing the functionality of a GameAgent.
 /// Most importantly, given a GameState, a GameAgent must be able to decide a GameMove.
 pub trait GameAgent<TState: GameState> {
@@ -147,10 +369,36 @@ pub trait GameAgent<TState: GameState> {
     /// so implementors can ignore this if they have no need for it.
     fn observe_action(&self, _player: PlayerColor, _action: TState::Action, _result: &TState) {}
 
+    /// Like pick_move(), but the agent may instead ask to take back its previous move.
+    /// Only runners that can undo moves invoke this, in place of pick_move().
+    /// The default implementation always plays the move selected by pick_move().
+    fn pick_turn(
+        &self,
+        state: &TState,
+        legal_moves: &[TState::Action],
+    ) -> TurnChoice<TState::Action> {
+        TurnChoice::Play(self.pick_move(state, legal_moves))
+    }
+
+    /// Invoked by the game runner when an action observed earlier is undone,
+    /// with the state that results from undoing it.
+    /// A default implementation is provided that does nothing.
+    fn observe_undo(&self, _player: PlayerColor, _action: TState::Action, _result: &TState) {}
+
     /// Returns the player color of this player.
     fn player_color(&self) -> PlayerColor;
 }
 
+/// An agent's choice for its turn, when it is allowed to take back moves.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TurnChoice<TAction> {
+    /// Play the given action.
+    Play(TAction),
+
+    /// Undo the game back to before this agent's previous move, and let it move again.
+    TakeBack,
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
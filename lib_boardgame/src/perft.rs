@@ -0,0 +1,46 @@
+//! Perft ("performance test"): counting the paths of legal moves from a position
+//! to a fixed depth. Comparing the counts against known values validates a game's
+//! move generation, and timing them measures its throughput.
+
+use crate::GameState;
+
+/// Counts the leaf nodes of the game tree from the given state to the given depth.
+/// A game that ends before the depth is reached counts as a single leaf,
+/// as in the published Othello counts.
+/// Passes count as moves, in games where passing is a legal move.
+pub fn perft<T: GameState>(state: &T, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    if state.is_game_over() {
+        return 1;
+    }
+
+    let legal_moves = state.legal_moves();
+
+    // The children are the leaves, so there's no need to create them.
+    if depth == 1 {
+        return legal_moves.len() as u64;
+    }
+
+    legal_moves
+        .iter()
+        .map(|&action| perft(&state.next_state(action), depth - 1))
+        .sum()
+}
+
+/// Like perft(), but broken down by the first move, in the order of legal_moves().
+/// When two move generators disagree, comparing their breakdowns (and descending into
+/// the moves whose counts differ) narrows down the position where they go wrong.
+pub fn perft_divide<T: GameState>(state: &T, depth: usize) -> Vec<(T::Action, u64)> {
+    if depth == 0 || state.is_game_over() {
+        return Vec::new();
+    }
+
+    state
+        .legal_moves()
+        .iter()
+        .map(|&action| (action, perft(&state.next_state(action), depth - 1)))
+        .collect()
+}
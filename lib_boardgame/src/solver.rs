@@ -0,0 +1,170 @@
+//! Exact solving of small games: every position reachable from a starting position
+//! is searched exhaustively, giving a table of results under perfect play.
+//! The table serves as an oracle for checking that agents pick optimal moves.
+
+use crate::{GameAgent, GameResult, GameState, PlayerColor, StateEncoding};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The result under perfect play of every position reachable from a starting position.
+pub struct Oracle<T: GameState> {
+    /// The results, keyed by each position's encoding.
+    results: HashMap<Vec<u8>, GameResult>,
+
+    /// Every solved position, in the order they were solved.
+    positions: Vec<T>,
+}
+
+/// How good the result is for the given player: a win beats a tie, which beats a loss.
+fn outcome_rank(result: GameResult, player: PlayerColor) -> u8 {
+    match result {
+        GameResult::Tie => 1,
+        _ if result.is_win_for_player(player) => 2,
+        _ => 0,
+    }
+}
+
+impl<T: GameState + StateEncoding> Oracle<T> {
+    /// Solves every position reachable from the given state.
+    /// The search visits each position once, so it's only practical for games
+    /// with a few million reachable positions at most.
+    pub fn solve(start: &T) -> Self {
+        let mut oracle = Self {
+            results: HashMap::new(),
+            positions: Vec::new(),
+        };
+
+        oracle.solve_from(start);
+
+        oracle
+    }
+
+    fn solve_from(&mut self, state: &T) -> GameResult {
+        let key = state.encode_state();
+
+        if let Some(&result) = self.results.get(&key) {
+            return result;
+        }
+
+        let result = match state.game_result() {
+            Some(result) => result,
+            None => {
+                let player = state.current_player_turn();
+
+                state
+                    .legal_moves()
+                    .iter()
+                    .map(|&action| self.solve_from(&state.next_state(action)))
+                    .max_by_key(|&result| outcome_rank(result, player))
+                    .expect("A game that isn't over must have a legal move.")
+            }
+        };
+
+        self.results.insert(key, result);
+        self.positions.push(state.clone());
+
+        result
+    }
+
+    /// The count of solved positions, including finished games.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether no positions were solved (never true, since the start is always solved).
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Every solved position, in the order they were solved (so finished games come first).
+    pub fn positions(&self) -> &[T] {
+        &self.positions
+    }
+
+    /// The result of the given position under perfect play,
+    /// or None if it isn't reachable from the solved starting position.
+    pub fn result(&self, state: &T) -> Option<GameResult> {
+        self.results.get(&state.encode_state()).copied()
+    }
+
+    /// The legal moves that keep the result of the given position under perfect play,
+    /// or None if it isn't reachable from the solved starting position.
+    pub fn optimal_moves(&self, state: &T) -> Option<Vec<T::Action>> {
+        let result = self.result(state)?;
+
+        let optimal = state
+            .legal_moves()
+            .iter()
+            .copied()
+            .filter(|&action| self.result(&state.next_state(action)) == Some(result))
+            .collect();
+
+        Some(optimal)
+    }
+
+    /// Asks an agent for its move in every solved position where some legal move
+    /// is worse than the others, and returns the moves that changed the result.
+    /// A fresh agent is created for each position (for the player to move),
+    /// since agents may keep state from one move to the next.
+    pub fn mistakes<A, F>(&self, mut new_agent: F) -> Vec<Mistake<T>>
+    where
+        A: GameAgent<T>,
+        F: FnMut(PlayerColor) -> A,
+    {
+        let mut mistakes = Vec::new();
+
+        for position in self.positions.iter().filter(|p| !p.is_game_over()) {
+            let legal_moves = position.legal_moves();
+            let optimal = self
+                .optimal_moves(position)
+                .expect("Every solved position has a result.");
+
+            // With no wrong choice, there's nothing to check.
+            if optimal.len() == legal_moves.len() {
+                continue;
+            }
+
+            let agent = new_agent(position.current_player_turn());
+            let chosen = agent.pick_move(position, legal_moves);
+
+            if !optimal.contains(&chosen) {
+                mistakes.push(Mistake {
+                    position: position.clone(),
+                    chosen,
+                    optimal,
+                });
+            }
+        }
+
+        mistakes
+    }
+}
+
+/// A move chosen by an agent that changes the result of the position under perfect play.
+#[derive(Clone, Debug)]
+pub struct Mistake<T: GameState> {
+    pub position: T,
+    pub chosen: T::Action,
+
+    /// The legal moves that would have kept the result.
+    pub optimal: Vec<T::Action>,
+}
+
+impl<T: GameState> fmt::Display for Mistake<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optimal = self
+            .optimal
+            .iter()
+            .map(|action| action.to_string())
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "{}\n{:?} chose {}, but only [{}] keep the result",
+            self.position.human_friendly(),
+            self.position.current_player_turn(),
+            self.chosen,
+            optimal.join(", ")
+        )
+    }
+}
@@ -1,12 +1,21 @@
-use lib_boardgame::{GameResult, GameState, PlayerColor};
+use lib_boardgame::{
+    split_position_text, GameResult, GameState, IllegalMoveError, IllegalMoveReason, Mobility,
+    MoveOrdering, PlayerColor, PositionError, PositionSetup, StateEncoding, UndoMove,
+};
 use std::fmt::Display;
 
-const GAME_WIDTH: usize = 7;
-const GAME_HEIGHT: usize = GAME_WIDTH - 1;
+/// The number of columns on the board.
+pub const GAME_WIDTH: usize = 7;
 
-fn is_in_range(col: usize, height: usize) -> bool {
-    col < GAME_WIDTH && height < GAME_HEIGHT
-}
+/// The number of pieces each column can hold.
+pub const GAME_HEIGHT: usize = GAME_WIDTH - 1;
+
+/// The pieces on a board W columns wide and H pieces tall,
+/// indexed by [height][col], with the bottom row first.
+pub type SizedConnectFourBoard<const W: usize, const H: usize> = [[ConnectFourPiece; W]; H];
+
+/// The pieces on the standard board.
+pub type ConnectFourBoard = SizedConnectFourBoard<GAME_WIDTH, GAME_HEIGHT>;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ConnectFourPiece {
@@ -30,7 +39,7 @@ impl Display for ConnectFourPiece {
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct ConnectFourAction {
     /// The col index where the piece will be dropped,
-    /// where 0 is the leftmost col and GAME_SIZE-1 is the rightmost.
+    /// where 0 is the leftmost col and the board width minus one is the rightmost.
     col: usize,
 }
 
@@ -45,6 +54,10 @@ impl lib_boardgame::GameAction for ConnectFourAction {
         // No such thing in this game
         false
     }
+
+    fn input_hint() -> String {
+        "column number (0 is leftmost)".to_owned()
+    }
 }
 
 impl std::str::FromStr for ConnectFourAction {
@@ -63,11 +76,12 @@ impl Display for ConnectFourAction {
     }
 }
 
+/// The state of a game of Connect Four played on a board W columns wide and H pieces tall.
 #[derive(Clone, Debug)]
-pub struct ConnectFourState {
+pub struct SizedConnectFourState<const W: usize, const H: usize> {
     player_turn: PlayerColor,
-    board: [[ConnectFourPiece; GAME_WIDTH]; GAME_HEIGHT],
-    col_cur_height: [usize; GAME_WIDTH],
+    board: SizedConnectFourBoard<W, H>,
+    col_cur_height: [usize; W],
 
     legal_moves: Vec<ConnectFourAction>,
     game_result: Option<GameResult>,
@@ -76,12 +90,23 @@ pub struct ConnectFourState {
     piece_count: usize,
 }
 
-impl ConnectFourState {
+/// The state of a game of Connect Four played on the standard 7x6 board.
+pub type ConnectFourState = SizedConnectFourState<GAME_WIDTH, GAME_HEIGHT>;
+
+impl<const W: usize, const H: usize> Default for SizedConnectFourState<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> SizedConnectFourState<W, H> {
     pub fn new() -> Self {
+        assert!(W > 0 && H > 0, "The board must have at least one position.");
+
         Self {
             player_turn: PlayerColor::Black,
-            col_cur_height: [0; GAME_WIDTH],
-            board: [[ConnectFourPiece::Empty; GAME_WIDTH]; GAME_HEIGHT],
+            col_cur_height: [0; W],
+            board: [[ConnectFourPiece::Empty; W]; H],
 
             legal_moves: Default::default(),
             game_result: None,
@@ -90,13 +115,23 @@ impl ConnectFourState {
     }
 
     /// Refresh the cached value of legal moves available from this state.
+    /// Once the game is won, there are none.
     fn update_legal_moves(&mut self) {
-        let legal = (0..GAME_WIDTH)
+        if self.game_result.is_some() {
+            self.legal_moves.clear();
+            return;
+        }
+
+        let legal = (0..W)
             .filter(|&i| !self.is_col_full(i))
-            .map(|i| ConnectFourAction::new(i))
+            .map(ConnectFourAction::new)
             .collect::<Vec<_>>();
 
-        std::mem::replace(&mut self.legal_moves, legal);
+        self.legal_moves = legal;
+    }
+
+    fn is_in_range(col: usize, height: usize) -> bool {
+        col < W && height < H
     }
 
     /// Returns the piece at the given position.
@@ -117,7 +152,7 @@ impl ConnectFourState {
 
     /// True if the column is full (has no room left for other pieces).
     fn is_col_full(&self, col: usize) -> bool {
-        self.col_height(col) >= GAME_HEIGHT
+        self.col_height(col) >= H
     }
 
     /// Increment the cached column height. Internal use only.
@@ -130,7 +165,7 @@ impl ConnectFourState {
     }
 
     fn update_end_game_result(&mut self) {
-        if self.piece_count >= GAME_HEIGHT * GAME_WIDTH {
+        if self.piece_count >= H * W {
             self.game_result = Some(GameResult::Tie);
         }
     }
@@ -140,7 +175,7 @@ impl ConnectFourState {
     pub fn drop_piece(&mut self, col: usize, piece: ConnectFourPiece) {
         let piece_height = self.col_height(col);
 
-        if piece_height >= GAME_HEIGHT {
+        if piece_height >= H {
             panic!(
                 "can't legally drop a piece in col {}, \
                  which already has height {} and has no more room.",
@@ -199,7 +234,10 @@ impl ConnectFourState {
                         let col: i32 = pos.x as i32 + (s * i * col_dir);
                         let height: i32 = pos.y as i32 + (s * i * height_dir);
 
-                        if col < 0 || height < 0 || !is_in_range(col as usize, height as usize) {
+                        if col < 0
+                            || height < 0
+                            || !Self::is_in_range(col as usize, height as usize)
+                        {
                             break;
                         }
 
@@ -222,14 +260,14 @@ impl ConnectFourState {
     }
 }
 
-impl Display for ConnectFourState {
+impl<const W: usize, const H: usize> Display for SizedConnectFourState<W, H> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut result = String::new();
 
-        for height in (0..GAME_HEIGHT).rev() {
+        for height in (0..H).rev() {
             result.push('|');
 
-            for col in 0..GAME_WIDTH {
+            for col in 0..W {
                 let piece = self.piece_at(col, height);
                 result.push_str(&format!("{}", piece));
                 result.push('|');
@@ -239,15 +277,272 @@ impl Display for ConnectFourState {
         }
 
         result.push(' ');
-        for col in 0..GAME_WIDTH {
-            result.push_str(&format!("{} ", col.to_string()));
+        for col in 0..W {
+            result.push_str(&format!("{} ", col));
         }
 
         write!(f, "{}", result)
     }
 }
 
-impl GameState for ConnectFourState {
+/// Encodings of a game result, for use in the StateEncoding.
+fn encode_result(result: Option<GameResult>) -> u8 {
+    match result {
+        None => 0,
+        Some(GameResult::BlackWins) => 1,
+        Some(GameResult::WhiteWins) => 2,
+        Some(GameResult::Tie) => 3,
+    }
+}
+
+fn decode_result(byte: u8) -> Option<Option<GameResult>> {
+    match byte {
+        0 => Some(None),
+        1 => Some(Some(GameResult::BlackWins)),
+        2 => Some(Some(GameResult::WhiteWins)),
+        3 => Some(Some(GameResult::Tie)),
+        _ => None,
+    }
+}
+
+impl<const W: usize, const H: usize> UndoMove for SizedConnectFourState<W, H> {
+    /// A move can only be made while the game is in progress,
+    /// so the column the piece was dropped in is all that's needed to undo it.
+    type Undo = ConnectFourAction;
+
+    fn make_move(&mut self, action: ConnectFourAction) -> ConnectFourAction {
+        self.apply_move(action);
+
+        action
+    }
+
+    fn undo_move(&mut self, undo: ConnectFourAction) {
+        let col = undo.col;
+        let height = self.col_height(col) - 1;
+
+        self.set_piece(col, height, ConnectFourPiece::Empty);
+        self.col_cur_height[col] -= 1;
+        self.piece_count -= 1;
+        self.game_result = None;
+        self.player_turn = self.player_turn.opponent();
+
+        self.update_legal_moves();
+    }
+}
+
+impl<const W: usize, const H: usize> Mobility for SizedConnectFourState<W, H> {
+    /// In Connect Four, either player may drop a piece into any column that isn't full.
+    fn legal_moves_for(&self, _player: PlayerColor) -> Vec<ConnectFourAction> {
+        self.legal_moves.clone()
+    }
+}
+
+impl<const W: usize, const H: usize> StateEncoding for SizedConnectFourState<W, H> {
+    /// Encodes the player to move and the game result,
+    /// followed by one byte per board position (0 for empty, 1 for black, 2 for red),
+    /// column by column from the bottom up.
+    fn encode_state(&self) -> Vec<u8> {
+        let mut bytes = vec![self.player_turn.to_byte(), encode_result(self.game_result)];
+
+        for col in 0..W {
+            for height in 0..H {
+                bytes.push(match self.piece_at(col, height) {
+                    ConnectFourPiece::Empty => 0,
+                    ConnectFourPiece::Black => 1,
+                    ConnectFourPiece::Red => 2,
+                });
+            }
+        }
+
+        bytes
+    }
+
+    fn decode_state(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 2 + W * H {
+            return None;
+        }
+
+        let mut state = Self::new();
+        state.player_turn = PlayerColor::from_byte(bytes[0])?;
+        state.game_result = decode_result(bytes[1])?;
+
+        for (col, cells) in bytes[2..].chunks(H).enumerate() {
+            for (height, &cell) in cells.iter().enumerate() {
+                let piece = match cell {
+                    0 => continue,
+                    1 => ConnectFourPiece::Black,
+                    2 => ConnectFourPiece::Red,
+                    _ => return None,
+                };
+
+                // Pieces can't float above an empty position.
+                if height != state.col_height(col) {
+                    return None;
+                }
+
+                state.set_piece(col, height, piece);
+                state.increment_col(col);
+                state.increment_piece_count();
+            }
+        }
+
+        state.update_legal_moves();
+
+        Some(state)
+    }
+}
+
+impl<const W: usize, const H: usize> PositionSetup for SizedConnectFourState<W, H> {
+    type Board = SizedConnectFourBoard<W, H>;
+
+    /// Creates a state from a board indexed by [height][col], with the bottom row first.
+    /// Pieces can't float above an empty position, Black always moves first
+    /// (so the piece counts must agree with the player to move),
+    /// and at most one player can have four in a row.
+    fn from_position(
+        board: SizedConnectFourBoard<W, H>,
+        side_to_move: PlayerColor,
+    ) -> Result<Self, PositionError> {
+        let mut state = Self::new();
+        let mut black_count: usize = 0;
+        let mut red_count = 0;
+
+        for col in 0..W {
+            for (height, row) in board.iter().enumerate() {
+                match row[col] {
+                    ConnectFourPiece::Empty => continue,
+                    ConnectFourPiece::Black => black_count += 1,
+                    ConnectFourPiece::Red => red_count += 1,
+                }
+
+                if height != state.col_height(col) {
+                    return Err(PositionError::Impossible(format!(
+                        "a piece in col {} floats above an empty position",
+                        col
+                    )));
+                }
+
+                state.set_piece(col, height, row[col]);
+                state.increment_col(col);
+                state.increment_piece_count();
+            }
+        }
+
+        let expected_turn = match black_count.checked_sub(red_count) {
+            Some(0) => PlayerColor::Black,
+            Some(1) => PlayerColor::White,
+            _ => {
+                return Err(PositionError::Impossible(format!(
+                    "{} black pieces and {} red pieces",
+                    black_count, red_count
+                )))
+            }
+        };
+
+        if side_to_move != expected_turn {
+            return Err(PositionError::Impossible(format!(
+                "{} black pieces and {} red pieces, but {:?} is to move",
+                black_count, red_count, side_to_move
+            )));
+        }
+
+        let has_four = |piece| {
+            (0..W).any(|x| {
+                (0..H).any(|y| {
+                    state.piece_at(x, y) == piece && state.is_pos_four_in_a_row(Position { x, y })
+                })
+            })
+        };
+
+        state.game_result = match (
+            has_four(ConnectFourPiece::Black),
+            has_four(ConnectFourPiece::Red),
+        ) {
+            (true, true) => {
+                return Err(PositionError::Impossible(
+                    "both players have four in a row".to_owned(),
+                ))
+            }
+            (true, false) => Some(GameResult::BlackWins),
+            (false, true) => Some(GameResult::WhiteWins),
+            (false, false) => None,
+        };
+
+        // The winner must have made the last move.
+        match (state.game_result, side_to_move) {
+            (Some(GameResult::BlackWins), PlayerColor::Black)
+            | (Some(GameResult::WhiteWins), PlayerColor::White) => {
+                return Err(PositionError::Impossible(
+                    "play continued after the game was won".to_owned(),
+                ))
+            }
+            _ => {}
+        }
+
+        state.player_turn = side_to_move;
+        state.update_legal_moves();
+        state.update_end_game_result();
+
+        Ok(state)
+    }
+
+    /// Parses the W*H squares, row by row from the top (as displayed), with '-' for empty,
+    /// 'X' for black, and 'O' for red, followed by the player to move ('X' or 'O').
+    fn parse_position(text: &str) -> Result<Self, PositionError> {
+        let (squares, side_to_move) = split_position_text(text, W * H)?;
+
+        let mut board: SizedConnectFourBoard<W, H> = [[ConnectFourPiece::Empty; W]; H];
+
+        for (i, c) in squares.into_iter().enumerate() {
+            board[H - 1 - i / W][i % W] = match c {
+                '-' | '.' => ConnectFourPiece::Empty,
+                'X' | 'x' => ConnectFourPiece::Black,
+                'O' | 'o' => ConnectFourPiece::Red,
+                _ => {
+                    return Err(PositionError::Malformed(format!(
+                        "unrecognized square: {:?}",
+                        c
+                    )))
+                }
+            };
+        }
+
+        Self::from_position(board, side_to_move)
+    }
+
+    fn position_string(&self) -> String {
+        let mut result = String::with_capacity(W * H + 2);
+
+        for height in (0..H).rev() {
+            for col in 0..W {
+                result.push(match self.piece_at(col, height) {
+                    ConnectFourPiece::Empty => '-',
+                    ConnectFourPiece::Black => 'X',
+                    ConnectFourPiece::Red => 'O',
+                });
+            }
+        }
+
+        result.push(' ');
+        result.push(match self.player_turn {
+            PlayerColor::Black => 'X',
+            PlayerColor::White => 'O',
+        });
+
+        result
+    }
+}
+
+impl<const W: usize, const H: usize> MoveOrdering for SizedConnectFourState<W, H> {
+    /// Prioritizes the center columns, which take part in the most possible connections.
+    fn move_priority(&self, action: ConnectFourAction) -> f32 {
+        let center = (W - 1) as f32 / 2.0;
+
+        -(action.col as f32 - center).abs()
+    }
+}
+
+impl<const W: usize, const H: usize> GameState for SizedConnectFourState<W, H> {
     type Action = ConnectFourAction;
 
     fn human_friendly(&self) -> String {
@@ -269,7 +564,7 @@ impl GameState for ConnectFourState {
         state
     }
 
-    fn legal_moves(&self, _player: PlayerColor) -> &[Self::Action] {
+    fn legal_moves(&self) -> &[Self::Action] {
         &self.legal_moves
     }
 
@@ -286,12 +581,36 @@ impl GameState for ConnectFourState {
         self.player_turn = self.player_turn.opponent();
     }
 
+    fn try_apply_move(&mut self, action: Self::Action) -> Result<(), IllegalMoveError> {
+        let illegal = |reason| Err(IllegalMoveError::new(action, reason));
+
+        if self.is_game_over() {
+            return illegal(IllegalMoveReason::GameOver);
+        }
+
+        if action.col >= W {
+            return illegal(IllegalMoveReason::OutOfBounds);
+        }
+
+        if self.is_col_full(action.col) {
+            return illegal(IllegalMoveReason::Occupied);
+        }
+
+        self.apply_move(action);
+
+        Ok(())
+    }
+
     fn current_player_turn(&self) -> PlayerColor {
         self.player_turn
     }
 
-    fn player_score(&self, _player: PlayerColor) -> usize {
-        unimplemented!()
+    /// Connect Four has no running score, so the winner scores 1 and everyone else scores 0.
+    fn player_score(&self, player: PlayerColor) -> usize {
+        match self.game_result {
+            Some(result) if result.is_win_for_player(player) => 1,
+            _ => 0,
+        }
     }
 
     fn skip_turn(&mut self) {
@@ -1,3 +1,39 @@
 mod connect_four;
 
 pub use crate::connect_four::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConnectFourState, SizedConnectFourState};
+    use lib_boardgame::{
+        conformance::assert_mobility_conformance, GameResult, GameState, Oracle, PositionSetup,
+    };
+
+    #[test]
+    fn random_playouts_expect_conformance() {
+        assert_mobility_conformance::<ConnectFourState>();
+        assert_mobility_conformance::<SizedConnectFourState<4, 4>>();
+    }
+
+    #[test]
+    fn solve_small_board_expects_draw_from_every_legal_position_count() {
+        let initial = SizedConnectFourState::<4, 4>::initial_state();
+        let oracle = Oracle::solve(&initial);
+
+        // Every legal position of the 4x4 board, including finished games.
+        assert_eq!(161_029, oracle.len());
+        assert_eq!(Some(GameResult::Tie), oracle.result(&initial));
+    }
+
+    #[test]
+    fn optimal_moves_when_four_available_expects_winning_move() {
+        let state = SizedConnectFourState::<4, 4>::parse_position("--------OOO-XXX- X").unwrap();
+        let oracle = Oracle::solve(&state);
+
+        assert_eq!(Some(GameResult::BlackWins), oracle.result(&state));
+        assert!(oracle
+            .optimal_moves(&state)
+            .unwrap()
+            .contains(&"3".parse().unwrap()));
+    }
+}
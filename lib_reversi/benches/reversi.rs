@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use lib_boardgame::{GameState, Mobility};
+use lib_reversi::{parse_move_sequence, ReversiState};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// A midgame position, with plenty of moves and flips available to both players.
+fn midgame_state() -> ReversiState {
+    let mut state = ReversiState::initial_state();
+    state.apply_moves(parse_move_sequence("f5d6c3d3c4f4f6f3e6e7d7c6b5c5b4").unwrap());
+
+    state
+}
+
+fn bench_apply_move(c: &mut Criterion) {
+    let state = midgame_state();
+    let actions = state.legal_moves().to_vec();
+
+    // Each legal move is applied to its own copy of the state, made outside the timing.
+    c.bench_function("reversi apply_move (every midgame move)", |b| {
+        b.iter_batched(
+            || vec![state.clone(); actions.len()],
+            |mut states| {
+                for (s, &action) in states.iter_mut().zip(&actions) {
+                    s.apply_move(action);
+                }
+
+                states
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_legal_moves(c: &mut Criterion) {
+    let initial = ReversiState::initial_state();
+    let midgame = midgame_state();
+
+    // The moves of the player to move are cached, so ask for the opponent's,
+    // which are generated from scratch.
+    c.bench_function("reversi calc_legal_moves (initial)", |b| {
+        b.iter(|| black_box(&initial).legal_moves_for(initial.current_player_turn().opponent()))
+    });
+
+    c.bench_function("reversi calc_legal_moves (midgame)", |b| {
+        b.iter(|| black_box(&midgame).legal_moves_for(midgame.current_player_turn().opponent()))
+    });
+}
+
+fn bench_playout(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    c.bench_function("reversi random playout", |b| {
+        b.iter(|| {
+            let mut state = ReversiState::initial_state();
+
+            while !state.is_game_over() {
+                let action = *state.legal_moves().choose(&mut rng).unwrap();
+                state.apply_move(action);
+            }
+
+            state
+        })
+    });
+}
+
+criterion_group!(benches, bench_apply_move, bench_legal_moves, bench_playout);
+criterion_main!(benches);
@@ -0,0 +1,407 @@
+//! Static evaluation of Reversi positions, built from classic hand-crafted features.
+//! Every feature is measured from one player's perspective, as that player's share
+//! minus their opponent's share, in the range [-1, 1]. The features are then
+//! combined using weights that depend on the phase of the game.
+
+use crate::{BoardPosition, ReversiPiece, SizedReversiAction, SizedReversiState};
+use lib_boardgame::{GameResult, GameState, Mobility, PlayerColor, StaticEvaluation};
+
+/// The value of a won game, which outweighs any heuristic evaluation.
+pub const WIN_VALUE: f32 = 1000.0;
+
+/// The stage of the game, judged by how much of the board is still empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GamePhase {
+    /// More than five eighths of the board is empty.
+    Opening,
+
+    /// Between one quarter and five eighths of the board is empty.
+    Midgame,
+
+    /// At most one quarter of the board is empty.
+    Endgame,
+}
+
+impl GamePhase {
+    /// The phase of the game in the given state.
+    pub fn of<const N: usize>(state: &SizedReversiState<N>) -> Self {
+        let squares = N * N;
+        let empty =
+            squares - state.disc_count(PlayerColor::Black) - state.disc_count(PlayerColor::White);
+
+        if empty * 8 > squares * 5 {
+            GamePhase::Opening
+        } else if empty * 4 > squares {
+            GamePhase::Midgame
+        } else {
+            GamePhase::Endgame
+        }
+    }
+}
+
+/// The features of a position, from one player's perspective.
+/// Each is the player's count minus the opponent's, divided by their sum
+/// (or 0 if neither player has any).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Features {
+    /// Discs on the board.
+    pub discs: f32,
+
+    /// Legal moves available (current mobility).
+    pub mobility: f32,
+
+    /// Empty squares next to an opponent's disc, where moves may later open up (potential mobility).
+    pub potential_mobility: f32,
+
+    /// Discs next to an empty square, which tend to give the opponent moves.
+    pub frontier: f32,
+
+    /// Occupied corners.
+    pub corners: f32,
+
+    /// Discs diagonally next to an empty corner, which tend to give that corner away.
+    pub x_squares: f32,
+
+    /// Discs orthogonally next to an empty corner, on the edge.
+    pub c_squares: f32,
+
+    /// Discs that can never be flipped (see SizedReversiState::stable_discs()).
+    pub stability: f32,
+
+    /// 1 if the player is due to make the last move of the game (if nobody passes),
+    /// and -1 otherwise.
+    pub parity: f32,
+}
+
+/// The weight of each feature, in the same order as the fields of Features.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FeatureWeights {
+    pub discs: f32,
+    pub mobility: f32,
+    pub potential_mobility: f32,
+    pub frontier: f32,
+    pub corners: f32,
+    pub x_squares: f32,
+    pub c_squares: f32,
+    pub stability: f32,
+    pub parity: f32,
+}
+
+/// Feature weights for each phase of the game.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhaseWeights {
+    pub opening: FeatureWeights,
+    pub midgame: FeatureWeights,
+    pub endgame: FeatureWeights,
+}
+
+impl PhaseWeights {
+    /// The weights to use in the given phase.
+    pub fn for_phase(&self, phase: GamePhase) -> &FeatureWeights {
+        match phase {
+            GamePhase::Opening => &self.opening,
+            GamePhase::Midgame => &self.midgame,
+            GamePhase::Endgame => &self.endgame,
+        }
+    }
+}
+
+impl Default for PhaseWeights {
+    /// Classic hand-tuned weights: early on, mobility and corners matter most
+    /// (and having fewer discs is an advantage), while by the endgame
+    /// the disc count and stability take over.
+    fn default() -> Self {
+        Self {
+            opening: FeatureWeights {
+                discs: -5.0,
+                mobility: 20.0,
+                potential_mobility: 10.0,
+                frontier: -10.0,
+                corners: 30.0,
+                x_squares: -15.0,
+                c_squares: -8.0,
+                stability: 10.0,
+                parity: 0.0,
+            },
+            midgame: FeatureWeights {
+                discs: 0.0,
+                mobility: 15.0,
+                potential_mobility: 8.0,
+                frontier: -8.0,
+                corners: 30.0,
+                x_squares: -12.0,
+                c_squares: -6.0,
+                stability: 15.0,
+                parity: 2.0,
+            },
+            endgame: FeatureWeights {
+                discs: 25.0,
+                mobility: 8.0,
+                potential_mobility: 2.0,
+                frontier: -2.0,
+                corners: 25.0,
+                x_squares: -5.0,
+                c_squares: -3.0,
+                stability: 20.0,
+                parity: 5.0,
+            },
+        }
+    }
+}
+
+impl Features {
+    /// Measures the features of the state, from the given player's perspective.
+    pub fn of<const N: usize>(state: &SizedReversiState<N>, player: PlayerColor) -> Self {
+        let opponent = player.opponent();
+        let theirs = ReversiPiece::from(opponent);
+
+        let mut frontier = [0, 0];
+        let mut potential_mobility = [0, 0];
+
+        for position in all_positions::<N>() {
+            let piece = state.get_piece(position);
+            let mut next_to_empty = false;
+            let mut next_to = [false, false];
+
+            for neighbor in neighbors::<N>(position) {
+                match state.get_piece(neighbor) {
+                    None => next_to_empty = true,
+                    Some(p) => next_to[(p == theirs) as usize] = true,
+                }
+            }
+
+            match piece {
+                Some(p) if next_to_empty => frontier[(p == theirs) as usize] += 1,
+                Some(_) => {}
+                None => {
+                    // An empty square next to the opponent's discs is potential mobility for the player.
+                    if next_to[1] {
+                        potential_mobility[0] += 1;
+                    }
+                    if next_to[0] {
+                        potential_mobility[1] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut corners = [0, 0];
+        let mut x_squares = [0, 0];
+        let mut c_squares = [0, 0];
+
+        for (corner, x_square, c_square_a, c_square_b) in corner_regions::<N>().iter().copied() {
+            let count = |counts: &mut [usize; 2], position| {
+                if let Some(p) = state.get_piece(position) {
+                    counts[(p == theirs) as usize] += 1;
+                }
+            };
+
+            if state.get_piece(corner).is_some() {
+                count(&mut corners, corner);
+            } else {
+                count(&mut x_squares, x_square);
+                count(&mut c_squares, c_square_a);
+                count(&mut c_squares, c_square_b);
+            }
+        }
+
+        let mobility = |color| {
+            state
+                .legal_moves_for(color)
+                .iter()
+                .filter(|&&a| a != SizedReversiAction::PassTurn)
+                .count()
+        };
+
+        let stable = state.stable_discs();
+        let squares = N * N;
+        let empty = squares - state.disc_count(player) - state.disc_count(opponent);
+        let player_moves_last = (empty % 2 == 1) == (state.current_player_turn() == player);
+
+        Self {
+            discs: relative(state.disc_count(player), state.disc_count(opponent)),
+            mobility: relative(mobility(player), mobility(opponent)),
+            potential_mobility: relative(potential_mobility[0], potential_mobility[1]),
+            frontier: relative(frontier[0], frontier[1]),
+            corners: relative(corners[0], corners[1]),
+            x_squares: relative(x_squares[0], x_squares[1]),
+            c_squares: relative(c_squares[0], c_squares[1]),
+            stability: relative(stable.count(player), stable.count(opponent)),
+            parity: if player_moves_last { 1.0 } else { -1.0 },
+        }
+    }
+
+    /// The weighted sum of the features.
+    pub fn weighted_sum(&self, weights: &FeatureWeights) -> f32 {
+        self.discs * weights.discs
+            + self.mobility * weights.mobility
+            + self.potential_mobility * weights.potential_mobility
+            + self.frontier * weights.frontier
+            + self.corners * weights.corners
+            + self.x_squares * weights.x_squares
+            + self.c_squares * weights.c_squares
+            + self.stability * weights.stability
+            + self.parity * weights.parity
+    }
+}
+
+/// Evaluates positions as a weighted combination of Features,
+/// with separate weights for each phase of the game.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Evaluator {
+    pub weights: PhaseWeights,
+}
+
+impl Evaluator {
+    pub fn with_weights(weights: PhaseWeights) -> Self {
+        Self { weights }
+    }
+
+    /// The value of the state for the given player, where higher is better.
+    /// Games that are over, or already decided by stable discs (see decided_result()),
+    /// are worth WIN_VALUE (plus the current disc margin) to the winner,
+    /// and the negation of that to the loser.
+    pub fn evaluate<const N: usize>(
+        &self,
+        state: &SizedReversiState<N>,
+        player: PlayerColor,
+    ) -> f32 {
+        if let Some(result) = state.decided_result() {
+            let margin =
+                state.player_score(player) as f32 - state.player_score(player.opponent()) as f32;
+
+            return match result {
+                GameResult::Tie => 0.0,
+                r if r.is_win_for_player(player) => WIN_VALUE + margin,
+                _ => -WIN_VALUE + margin,
+            };
+        }
+
+        let weights = self.weights.for_phase(GamePhase::of(state));
+
+        Features::of(state, player).weighted_sum(weights)
+    }
+}
+
+impl<const N: usize> StaticEvaluation for SizedReversiState<N> {
+    /// Evaluates the state with the default Evaluator.
+    fn evaluate(&self, player: PlayerColor) -> f32 {
+        Evaluator::default().evaluate(self, player)
+    }
+}
+
+/// The player's share minus the opponent's share of the total, or 0 if the total is 0.
+fn relative(mine: usize, theirs: usize) -> f32 {
+    if mine + theirs == 0 {
+        0.0
+    } else {
+        (mine as f32 - theirs as f32) / (mine + theirs) as f32
+    }
+}
+
+fn all_positions<const N: usize>() -> impl Iterator<Item = BoardPosition> {
+    (0..N).flat_map(|col| (0..N).map(move |row| BoardPosition::new(col, row)))
+}
+
+/// The (up to 8) positions surrounding the given position.
+fn neighbors<const N: usize>(position: BoardPosition) -> impl Iterator<Item = BoardPosition> {
+    let (col, row) = (position.col() as isize, position.row() as isize);
+
+    (-1..=1)
+        .flat_map(move |dc| (-1..=1).map(move |dr| (col + dc, row + dr)))
+        .filter(move |&(c, r)| (c, r) != (col, row))
+        .filter(|&(c, r)| c >= 0 && r >= 0 && (c as usize) < N && (r as usize) < N)
+        .map(|(c, r)| BoardPosition::new(c as usize, r as usize))
+}
+
+/// Each corner, with its X-square and its two C-squares.
+fn corner_regions<const N: usize>(
+) -> [(BoardPosition, BoardPosition, BoardPosition, BoardPosition); 4] {
+    let last = N - 1;
+    let inward = |i: usize| if i == 0 { 1 } else { last - 1 };
+    let region = |col, row| {
+        (
+            BoardPosition::new(col, row),
+            BoardPosition::new(inward(col), inward(row)),
+            BoardPosition::new(inward(col), row),
+            BoardPosition::new(col, inward(row)),
+        )
+    };
+
+    [
+        region(0, 0),
+        region(0, last),
+        region(last, 0),
+        region(last, last),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReversiState;
+    use lib_boardgame::PositionSetup;
+
+    #[test]
+    fn initial_position_expects_symmetric_features() {
+        let state = ReversiState::initial_state();
+
+        let black = Features::of(&state, PlayerColor::Black);
+
+        assert_eq!(GamePhase::Opening, GamePhase::of(&state));
+        assert_eq!(0.0, black.discs);
+        assert_eq!(0.0, black.mobility);
+        assert_eq!(0.0, black.corners);
+        assert_eq!(0.0, black.frontier);
+        assert_eq!(
+            state.evaluate(PlayerColor::Black),
+            state.evaluate(PlayerColor::White)
+        );
+    }
+
+    #[test]
+    fn corners_and_stable_edges_expect_advantage() {
+        // Black holds the a1 corner and the edge run b1-c1; White sits on the g2 X-square next to the empty h1 corner.
+        let text = [
+            "***-----",
+            "------O-",
+            "--------",
+            "---O*---",
+            "---*O---",
+            "--------",
+            "--------",
+            "-------- O",
+        ]
+        .concat();
+        let state = ReversiState::parse_position(&text).unwrap();
+
+        let black = Features::of(&state, PlayerColor::Black);
+        let white = Features::of(&state, PlayerColor::White);
+
+        assert_eq!(1.0, black.corners);
+        assert_eq!(1.0, black.stability);
+        assert_eq!(-1.0, white.stability);
+        assert_eq!(-1.0, black.x_squares);
+        assert_eq!(1.0, white.x_squares);
+        assert!(state.evaluate(PlayerColor::Black) > state.evaluate(PlayerColor::White));
+    }
+
+    #[test]
+    fn finished_game_expects_win_value() {
+        let text = [
+            "********",
+            "********",
+            "********",
+            "***OO***",
+            "***OO***",
+            "********",
+            "********",
+            "******** *",
+        ]
+        .concat();
+        let state = ReversiState::parse_position(&text).unwrap();
+
+        assert_eq!(WIN_VALUE + 56.0, state.evaluate(PlayerColor::Black));
+        assert_eq!(-WIN_VALUE - 56.0, state.evaluate(PlayerColor::White));
+    }
+}
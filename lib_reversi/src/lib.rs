@@ -1,10 +1,23 @@
+mod evaluation;
+mod notation;
+mod pattern;
 mod reversi_action;
 mod reversi_board;
 mod reversi_gamestate;
+mod stability;
 mod util;
+mod variant;
 
-use reversi_board::{Board, Directions, BOARD_SIZE};
+use reversi_board::Directions;
 
-pub use reversi_action::ReversiPlayerAction;
-pub use reversi_board::{BoardPosition, ReversiPiece};
-pub use reversi_gamestate::ReversiState;
+pub use evaluation::{Evaluator, FeatureWeights, Features, GamePhase, PhaseWeights, WIN_VALUE};
+pub use notation::{format_move_sequence, parse_move_sequence, NotationError};
+pub use pattern::{
+    label_positions, standard_patterns, Pattern, PatternEvaluator, TrainingSample, WeightsError,
+    MAX_BOARD_SIZE, MAX_PATTERNS, MAX_PATTERN_SQUARES, MAX_PHASES, MAX_WEIGHTS,
+};
+pub use reversi_action::{ReversiPlayerAction, SizedReversiAction};
+pub use reversi_board::{Board, BoardPosition, ReversiPiece, SizedBoard, BOARD_SIZE};
+pub use reversi_gamestate::{ReversiState, ReversiUndo, SizedReversiState};
+pub use stability::StableDiscs;
+pub use variant::{PassRule, ReversiVariant, Scoring, StartingPosition};
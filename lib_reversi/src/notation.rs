@@ -0,0 +1,199 @@
+//! Standard Othello notation: columns are lettered 'a' onwards from the left,
+//! and rows are numbered from 1 at the top, so "a1" is the top-left corner
+//! and "f5" is one of Black's four opening moves on the standard board.
+//! Since rows count from the top, a square's notation depends on the size of the board.
+//! A turn pass is written as "pass" (or NBoard's "pa").
+
+use crate::{BoardPosition, SizedReversiAction};
+use std::fmt;
+
+/// An error from parsing a move, or a sequence of moves, in Othello notation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotationError {
+    input: String,
+}
+
+impl NotationError {
+    fn new(input: &str) -> Self {
+        Self {
+            input: input.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Didn't recognize {:?} as a move (expected e.g. \"f5\", or \"pass\")",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl BoardPosition {
+    /// Parses a square in Othello notation (e.g. "f5") on a board of the given size, ignoring case.
+    pub fn from_notation(s: &str, board_size: usize) -> Result<Self, NotationError> {
+        let lower = s.trim().to_lowercase();
+        let mut chars = lower.chars();
+
+        let col = match chars.next() {
+            Some(letter @ 'a'..='z') => letter as usize - 'a' as usize,
+            _ => return Err(NotationError::new(s)),
+        };
+
+        let rank = match chars.as_str().parse::<usize>() {
+            Ok(rank) if chars.as_str().chars().all(|c| c.is_ascii_digit()) => rank,
+            _ => return Err(NotationError::new(s)),
+        };
+
+        if col >= board_size || rank == 0 || rank > board_size {
+            return Err(NotationError::new(s));
+        }
+
+        Ok(BoardPosition::new(col, board_size - rank))
+    }
+
+    /// This square in Othello notation (e.g. "f5") on a board of the given size.
+    pub fn to_notation(self, board_size: usize) -> String {
+        let letter = (b'a' + self.col as u8) as char;
+
+        format!("{}{}", letter, board_size - self.row)
+    }
+}
+
+/// Parses a run of moves with no separators, such as "f5d6c3", ignoring case.
+/// Whitespace and commas between moves are also accepted.
+pub fn parse_move_sequence<const N: usize>(
+    s: &str,
+) -> Result<Vec<SizedReversiAction<N>>, NotationError> {
+    let lower = s.to_lowercase();
+    let mut rest = lower.as_str();
+    let mut moves = Vec::new();
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+
+        if rest.is_empty() {
+            return Ok(moves);
+        }
+
+        if let Some(after) = rest
+            .strip_prefix("pass")
+            .or_else(|| rest.strip_prefix("pa"))
+        {
+            moves.push(SizedReversiAction::PassTurn);
+            rest = after;
+            continue;
+        }
+
+        // A letter, followed by the digits of the row.
+        let letter_len = rest.chars().next().map_or(0, char::len_utf8);
+        let digits = rest[letter_len..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .count();
+        let (square, after) = rest.split_at(letter_len + digits);
+
+        let position =
+            BoardPosition::from_notation(square, N).map_err(|_| NotationError::new(s))?;
+        moves.push(SizedReversiAction::Move { position });
+        rest = after;
+    }
+}
+
+/// Formats moves as a run with no separators, such as "f5d6c3".
+pub fn format_move_sequence<const N: usize>(moves: &[SizedReversiAction<N>]) -> String {
+    moves.iter().map(|m| m.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReversiPlayerAction;
+
+    fn action(col: usize, row: usize) -> ReversiPlayerAction {
+        ReversiPlayerAction::Move {
+            position: BoardPosition::new(col, row),
+        }
+    }
+
+    #[test]
+    fn notation_expects_rank_one_at_top() {
+        assert_eq!("a1", BoardPosition::new(0, 7).to_notation(8));
+        assert_eq!("h8", BoardPosition::new(7, 0).to_notation(8));
+        assert_eq!("f5", BoardPosition::new(5, 3).to_notation(8));
+        assert_eq!("a1", BoardPosition::new(0, 5).to_notation(6));
+
+        assert_eq!(
+            Ok(BoardPosition::new(5, 3)),
+            BoardPosition::from_notation("F5", 8)
+        );
+        assert_eq!(
+            Ok(BoardPosition::new(0, 7)),
+            BoardPosition::from_notation(" a1 ", 8)
+        );
+        assert_eq!(
+            Ok(BoardPosition::new(0, 5)),
+            BoardPosition::from_notation("a1", 6)
+        );
+    }
+
+    #[test]
+    fn from_notation_expects_errors_on_bad_input() {
+        for bad in &["", "f", "f0", "f9", "i1", "5f", "f5x", "ff5", "f+5"] {
+            assert!(
+                BoardPosition::from_notation(bad, 8).is_err(),
+                "Expected an error for {:?}",
+                bad
+            );
+        }
+
+        assert!(BoardPosition::from_notation("g1", 6).is_err());
+        assert!(BoardPosition::from_notation("a7", 6).is_err());
+    }
+
+    #[test]
+    fn input_hint_expects_board_corners() {
+        use crate::SizedReversiAction;
+        use lib_boardgame::GameAction;
+
+        assert_eq!("a1-h8 or pass", ReversiPlayerAction::input_hint());
+        assert_eq!("a1-f6 or pass", SizedReversiAction::<6>::input_hint());
+
+        for hinted in &["a1", "f6", "pass"] {
+            assert!(hinted.parse::<SizedReversiAction<6>>().is_ok());
+        }
+    }
+
+    #[test]
+    fn move_sequence_round_trips() {
+        let moves = parse_move_sequence("F5d6C3pass PA").unwrap();
+
+        assert_eq!(
+            vec![
+                action(5, 3),
+                action(3, 2),
+                action(2, 5),
+                ReversiPlayerAction::PassTurn,
+                ReversiPlayerAction::PassTurn,
+            ],
+            moves
+        );
+        assert_eq!("f5d6c3passpass", format_move_sequence(&moves));
+        assert_eq!(
+            Ok(moves.clone()),
+            parse_move_sequence(&format_move_sequence(&moves))
+        );
+    }
+
+    #[test]
+    fn parse_move_sequence_expects_errors_on_bad_input() {
+        assert!(parse_move_sequence::<8>("f5d9").is_err());
+        assert!(parse_move_sequence::<8>("f5z").is_err());
+        assert!(parse_move_sequence::<6>("f5g6").is_err());
+        assert_eq!(Ok(vec![]), parse_move_sequence::<8>(""));
+    }
+}
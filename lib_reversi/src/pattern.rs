@@ -0,0 +1,710 @@
+//! Pattern-based evaluation, in the style of Logistello: the board is read through
+//! a set of patterns (edges, corners, diagonals...), each of which indexes a table of
+//! learned weights by the configuration of discs on its squares.
+//! Every pattern is applied in all of its distinct rotations and reflections,
+//! which share one table, and each phase of the game has its own set of tables.
+//! The weights predict the final disc margin for the player being evaluated,
+//! and are fitted offline from labelled positions (see train()).
+
+use crate::{BoardPosition, NotationError, ReversiPiece, SizedReversiAction, SizedReversiState};
+use lib_boardgame::{GameState, PlayerColor};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Identifies a weights file, ahead of its version.
+const MAGIC: &[u8; 4] = b"RVPW";
+
+/// Bumped whenever the layout of a weights file changes,
+/// so older files are rejected instead of misread.
+const FORMAT_VERSION: u32 = 1;
+
+/// The most squares a pattern may cover, which keeps each table under a million entries.
+pub const MAX_PATTERN_SQUARES: usize = 12;
+
+// Limits on an evaluator's shape, so a corrupt weights file is rejected
+// instead of allocating whatever its header claims.
+
+/// The largest board an evaluator may be built for.
+pub const MAX_BOARD_SIZE: usize = 16;
+
+/// The most patterns an evaluator may use.
+pub const MAX_PATTERNS: usize = 64;
+
+/// The most game phases an evaluator may have.
+pub const MAX_PHASES: usize = 64;
+
+/// The most weights an evaluator may hold across all its tables (256 MB of f32s).
+pub const MAX_WEIGHTS: usize = 1 << 26;
+
+/// An error from building a pattern evaluator, or from saving or loading its weights.
+#[derive(Debug)]
+pub enum WeightsError {
+    Io(std::io::Error),
+
+    /// The patterns, or the data of a weights file, are not valid.
+    Format(String),
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightsError::Io(e) => write!(f, "I/O error: {}", e),
+            WeightsError::Format(msg) => write!(f, "Invalid pattern weights: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WeightsError {}
+
+impl From<std::io::Error> for WeightsError {
+    fn from(e: std::io::Error) -> Self {
+        WeightsError::Io(e)
+    }
+}
+
+/// Maps a square's (col, row) to its image, given the last index of the board.
+type Symmetry = fn(usize, usize, usize) -> (usize, usize);
+
+/// An ordered set of squares, whose discs together select one entry of a weight table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    squares: Vec<BoardPosition>,
+}
+
+impl Pattern {
+    pub fn new(squares: Vec<BoardPosition>) -> Self {
+        Self { squares }
+    }
+
+    /// Parses a pattern from whitespace-separated squares in Othello notation, e.g. "a1 b1 c1".
+    pub fn from_notation(squares: &str, board_size: usize) -> Result<Self, NotationError> {
+        squares
+            .split_whitespace()
+            .map(|s| BoardPosition::from_notation(s, board_size))
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+
+    pub fn squares(&self) -> &[BoardPosition] {
+        &self.squares
+    }
+
+    /// The number of entries in this pattern's table: one per configuration of its squares.
+    pub fn table_size(&self) -> usize {
+        3usize.pow(self.squares.len() as u32)
+    }
+
+    /// The distinct images of this pattern under the eight symmetries of the board.
+    /// Only identical images are merged, so a line that maps onto itself reversed
+    /// is read in both directions, keeping the evaluation itself symmetric.
+    fn images(&self, board_size: usize) -> Vec<Vec<BoardPosition>> {
+        let last = board_size - 1;
+        let symmetries: [Symmetry; 8] = [
+            |c, r, _| (c, r),
+            |c, r, l| (l - c, r),
+            |c, r, l| (c, l - r),
+            |c, r, l| (l - c, l - r),
+            |c, r, _| (r, c),
+            |c, r, l| (l - r, c),
+            |c, r, l| (r, l - c),
+            |c, r, l| (l - r, l - c),
+        ];
+
+        let mut images: Vec<Vec<BoardPosition>> = Vec::new();
+
+        for symmetry in symmetries.iter() {
+            let image = self
+                .squares
+                .iter()
+                .map(|p| {
+                    let (col, row) = symmetry(p.col, p.row, last);
+                    BoardPosition::new(col, row)
+                })
+                .collect::<Vec<_>>();
+
+            if !images.contains(&image) {
+                images.push(image);
+            }
+        }
+
+        images
+    }
+}
+
+/// The standard pattern set for the 8x8 board, in the spirit of Logistello's:
+/// the edges with their X-squares, two corner regions, the inner lines and the diagonals.
+pub fn standard_patterns() -> Vec<Pattern> {
+    [
+        "a1 b1 c1 d1 e1 f1 g1 h1 b2 g2",
+        "a1 b1 c1 a2 b2 c2 a3 b3 c3",
+        "a1 b1 c1 d1 e1 a2 b2 c2 d2 e2",
+        "a2 b2 c2 d2 e2 f2 g2 h2",
+        "a3 b3 c3 d3 e3 f3 g3 h3",
+        "a4 b4 c4 d4 e4 f4 g4 h4",
+        "a1 b2 c3 d4 e5 f6 g7 h8",
+        "a2 b3 c4 d5 e6 f7 g8",
+        "a3 b4 c5 d6 e7 f8",
+        "a4 b5 c6 d7 e8",
+        "a5 b4 c3 d2 e1",
+    ]
+    .iter()
+    .map(|s| Pattern::from_notation(s, 8).expect("The standard patterns must be valid."))
+    .collect()
+}
+
+/// The features of one position for training: its phase, and the table entry selected
+/// by each image of each pattern, as (pattern index, configuration index).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainingSample {
+    pub phase: usize,
+    pub entries: Vec<(usize, usize)>,
+
+    /// The value the evaluation should predict, e.g. the final disc margin.
+    pub target: f32,
+}
+
+/// Evaluates positions by summing the weights of the pattern configurations they contain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternEvaluator {
+    board_size: usize,
+    patterns: Vec<Pattern>,
+
+    /// The images of each pattern, precomputed for the board size.
+    images: Vec<Vec<Vec<BoardPosition>>>,
+
+    /// The weights, indexed by phase, then pattern, then configuration.
+    tables: Vec<Vec<Vec<f32>>>,
+}
+
+impl PatternEvaluator {
+    /// Creates an evaluator with all weights zero, for the given board size,
+    /// patterns and number of game phases.
+    pub fn new(
+        board_size: usize,
+        patterns: Vec<Pattern>,
+        phases: usize,
+    ) -> Result<Self, WeightsError> {
+        if board_size == 0 || board_size > MAX_BOARD_SIZE {
+            return Err(WeightsError::Format(format!(
+                "the board size must be 1 to {}",
+                MAX_BOARD_SIZE
+            )));
+        }
+
+        if phases == 0 || phases > MAX_PHASES {
+            return Err(WeightsError::Format(format!(
+                "there must be 1 to {} phases",
+                MAX_PHASES
+            )));
+        }
+
+        if patterns.len() > MAX_PATTERNS {
+            return Err(WeightsError::Format(format!(
+                "there may be at most {} patterns",
+                MAX_PATTERNS
+            )));
+        }
+
+        for (i, pattern) in patterns.iter().enumerate() {
+            let squares = pattern.squares();
+
+            if squares.is_empty() || squares.len() > MAX_PATTERN_SQUARES {
+                return Err(WeightsError::Format(format!(
+                    "pattern {} must have 1 to {} squares",
+                    i, MAX_PATTERN_SQUARES
+                )));
+            }
+
+            if squares
+                .iter()
+                .any(|p| p.col >= board_size || p.row >= board_size)
+            {
+                return Err(WeightsError::Format(format!(
+                    "pattern {} is off the board",
+                    i
+                )));
+            }
+
+            if (1..squares.len()).any(|j| squares[..j].contains(&squares[j])) {
+                return Err(WeightsError::Format(format!(
+                    "pattern {} repeats a square",
+                    i
+                )));
+            }
+        }
+
+        let weights = phases * patterns.iter().map(Pattern::table_size).sum::<usize>();
+        if weights > MAX_WEIGHTS {
+            return Err(WeightsError::Format(format!(
+                "{} weights exceeds the limit of {}",
+                weights, MAX_WEIGHTS
+            )));
+        }
+
+        let images = patterns.iter().map(|p| p.images(board_size)).collect();
+        let tables = (0..phases)
+            .map(|_| patterns.iter().map(|p| vec![0.0; p.table_size()]).collect())
+            .collect();
+
+        Ok(Self {
+            board_size,
+            patterns,
+            images,
+            tables,
+        })
+    }
+
+    /// An untrained evaluator using the standard pattern set on the 8x8 board.
+    pub fn standard(phases: usize) -> Self {
+        Self::new(8, standard_patterns(), phases).expect("The standard patterns must be valid.")
+    }
+
+    pub fn board_size(&self) -> usize {
+        self.board_size
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    pub fn phases(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// The phase of the game in the given state, by how full the board is,
+    /// from 0 (the initial position) to phases() - 1 (a full board).
+    pub fn phase_of<const N: usize>(&self, state: &SizedReversiState<N>) -> usize {
+        let discs = state.disc_count(PlayerColor::Black) + state.disc_count(PlayerColor::White);
+        let played = discs.saturating_sub(4);
+        let playable = N * N - 4;
+
+        (played * self.phases() / (playable + 1)).min(self.phases() - 1)
+    }
+
+    /// The table entry selected by each image of each pattern in the given state,
+    /// as (pattern index, configuration index), from the given player's perspective.
+    pub fn entries<const N: usize>(
+        &self,
+        state: &SizedReversiState<N>,
+        player: PlayerColor,
+    ) -> Vec<(usize, usize)> {
+        assert_eq!(
+            N, self.board_size,
+            "The evaluator's patterns are for a different board size."
+        );
+
+        let mine = ReversiPiece::from(player);
+        let mut entries = Vec::new();
+
+        for (pattern, images) in self.images.iter().enumerate() {
+            for image in images {
+                // Each square is a base-3 digit: empty, the player's disc, or the opponent's.
+                let index = image.iter().fold(0, |index, &p| {
+                    let digit = match state.get_piece(p) {
+                        None => 0,
+                        Some(piece) if piece == mine => 1,
+                        Some(_) => 2,
+                    };
+
+                    index * 3 + digit
+                });
+
+                entries.push((pattern, index));
+            }
+        }
+
+        entries
+    }
+
+    /// The predicted final disc margin for the given player.
+    /// Finished games are given their actual margin.
+    pub fn evaluate<const N: usize>(
+        &self,
+        state: &SizedReversiState<N>,
+        player: PlayerColor,
+    ) -> f32 {
+        if state.is_game_over() {
+            return margin(state, player);
+        }
+
+        let tables = &self.tables[self.phase_of(state)];
+
+        self.entries(state, player)
+            .into_iter()
+            .map(|(pattern, index)| tables[pattern][index])
+            .sum()
+    }
+
+    /// Extracts the training features of the state from the given player's perspective.
+    pub fn sample<const N: usize>(
+        &self,
+        state: &SizedReversiState<N>,
+        player: PlayerColor,
+        target: f32,
+    ) -> TrainingSample {
+        TrainingSample {
+            phase: self.phase_of(state),
+            entries: self.entries(state, player),
+            target,
+        }
+    }
+
+    fn predict(&self, sample: &TrainingSample) -> f32 {
+        let tables = &self.tables[sample.phase];
+
+        sample
+            .entries
+            .iter()
+            .map(|&(pattern, index)| tables[pattern][index])
+            .sum()
+    }
+
+    /// The mean squared error of the evaluator's predictions over the samples.
+    pub fn mean_squared_error(&self, samples: &[TrainingSample]) -> f32 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let total: f32 = samples
+            .iter()
+            .map(|s| (s.target - self.predict(s)).powi(2))
+            .sum();
+
+        total / samples.len() as f32
+    }
+
+    /// Fits the weights to the samples by least squares, using stochastic gradient descent:
+    /// for each sample in turn, every weight it selects is moved towards reducing its error.
+    /// The step is divided among the selected weights, so the learning rate is roughly
+    /// the fraction of each sample's error corrected at a time.
+    /// Returns the mean squared error after training.
+    pub fn train(&mut self, samples: &[TrainingSample], epochs: usize, learning_rate: f32) -> f32 {
+        for _ in 0..epochs {
+            for sample in samples {
+                if sample.entries.is_empty() {
+                    continue;
+                }
+
+                let error = sample.target - self.predict(sample);
+                let step = learning_rate * error / sample.entries.len() as f32;
+                let tables = &mut self.tables[sample.phase];
+
+                for &(pattern, index) in &sample.entries {
+                    tables[pattern][index] += step;
+                }
+            }
+        }
+
+        self.mean_squared_error(samples)
+    }
+
+    /// Writes the patterns and weights in a compact binary form.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), WeightsError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&[self.board_size as u8])?;
+        writer.write_all(&(self.phases() as u32).to_le_bytes())?;
+        writer.write_all(&(self.patterns.len() as u32).to_le_bytes())?;
+
+        for pattern in &self.patterns {
+            writer.write_all(&[pattern.squares.len() as u8])?;
+
+            for p in &pattern.squares {
+                writer.write_all(&[p.col as u8, p.row as u8])?;
+            }
+        }
+
+        for table in self.tables.iter().flatten() {
+            for weight in table {
+                writer.write_all(&weight.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads patterns and weights written by write_to(), which must be for the given board size
+    /// (the size of the states the evaluator will be used on).
+    pub fn read_from<R: Read>(mut reader: R, board_size: usize) -> Result<Self, WeightsError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(WeightsError::Format(
+                "not a pattern weights file".to_owned(),
+            ));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != FORMAT_VERSION {
+            return Err(WeightsError::Format(format!(
+                "unsupported format version {}",
+                version
+            )));
+        }
+
+        let saved_board_size = read_u8(&mut reader)? as usize;
+        if saved_board_size != board_size {
+            return Err(WeightsError::Format(format!(
+                "the weights are for a {0}x{0} board, not {1}x{1}",
+                saved_board_size, board_size
+            )));
+        }
+
+        let phases = read_u32(&mut reader)? as usize;
+        let pattern_count = read_u32(&mut reader)? as usize;
+
+        // The rest is checked by new(), but the pattern count bounds what's read before then.
+        if pattern_count > MAX_PATTERNS {
+            return Err(WeightsError::Format(format!(
+                "there may be at most {} patterns",
+                MAX_PATTERNS
+            )));
+        }
+
+        let mut patterns = Vec::with_capacity(pattern_count);
+        for _ in 0..pattern_count {
+            let len = read_u8(&mut reader)? as usize;
+            let mut squares = Vec::with_capacity(len);
+
+            for _ in 0..len {
+                let col = read_u8(&mut reader)? as usize;
+                let row = read_u8(&mut reader)? as usize;
+                squares.push(BoardPosition::new(col, row));
+            }
+
+            patterns.push(Pattern::new(squares));
+        }
+
+        let mut evaluator = Self::new(board_size, patterns, phases)?;
+
+        for weight in evaluator.tables.iter_mut().flatten().flatten() {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            *weight = f32::from_le_bytes(bytes);
+        }
+
+        Ok(evaluator)
+    }
+
+    /// Saves the weights to the file at the given path, replacing the file if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), WeightsError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Loads weights for the given board size from the file at the given path.
+    pub fn load(path: impl AsRef<Path>, board_size: usize) -> Result<Self, WeightsError> {
+        Self::read_from(BufReader::new(File::open(path)?), board_size)
+    }
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, WeightsError> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, WeightsError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// The given player's score minus their opponent's.
+fn margin<const N: usize>(state: &SizedReversiState<N>, player: PlayerColor) -> f32 {
+    state.player_score(player) as f32 - state.player_score(player.opponent()) as f32
+}
+
+/// Replays a finished game record from the initial position, labelling every position
+/// before the end with the final margin for the player to move there.
+/// Returns None if a move is illegal, or the game doesn't reach its end.
+pub fn label_positions<const N: usize>(
+    moves: &[SizedReversiAction<N>],
+) -> Option<Vec<(SizedReversiState<N>, PlayerColor, f32)>> {
+    let mut state = SizedReversiState::<N>::initial_state();
+    let mut positions = Vec::with_capacity(moves.len());
+
+    for &action in moves {
+        positions.push(state.clone());
+        state.try_apply_move(action).ok()?;
+    }
+
+    if !state.is_game_over() {
+        return None;
+    }
+
+    let labelled = positions
+        .into_iter()
+        .map(|position| {
+            let player = position.current_player_turn();
+            let label = margin(&state, player);
+
+            (position, player, label)
+        })
+        .collect();
+
+    Some(labelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_move_sequence, ReversiState};
+
+    #[test]
+    fn images_expect_distinct_symmetries() {
+        let edge = Pattern::from_notation("a1 b1 c1 d1 e1 f1 g1 h1 b2 g2", 8).unwrap();
+        let corner = Pattern::from_notation("a1 b1 c1 a2 b2 c2 a3 b3 c3", 8).unwrap();
+        let corner_2x5 = Pattern::from_notation("a1 b1 c1 d1 e1 a2 b2 c2 d2 e2", 8).unwrap();
+        let diagonal = Pattern::from_notation("a1 b2 c3 d4 e5 f6 g7 h8", 8).unwrap();
+
+        assert_eq!(8, edge.images(8).len());
+        assert_eq!(8, corner.images(8).len());
+        assert_eq!(8, corner_2x5.images(8).len());
+        assert_eq!(4, diagonal.images(8).len());
+    }
+
+    #[test]
+    fn symmetric_positions_expect_equal_evaluations() {
+        let mut evaluator = PatternEvaluator::standard(2);
+        let state = ReversiState::initial_state();
+
+        // Train on the position after f5, then check its mirror image (after e6) agrees.
+        let after_f5 = state.next_state(parse_move_sequence("f5").unwrap()[0]);
+        let after_e6 = state.next_state(parse_move_sequence("e6").unwrap()[0]);
+        let sample = evaluator.sample(&after_f5, PlayerColor::White, 3.0);
+        evaluator.train(&[sample], 200, 0.1);
+
+        let f5_value = evaluator.evaluate(&after_f5, PlayerColor::White);
+        let e6_value = evaluator.evaluate(&after_e6, PlayerColor::White);
+
+        assert!((f5_value - 3.0).abs() < 0.01, "f5 value was {}", f5_value);
+        assert!((f5_value - e6_value).abs() < 0.001);
+    }
+
+    #[test]
+    fn train_expects_reduced_error() {
+        let mut evaluator = PatternEvaluator::standard(4);
+        let mut samples = Vec::new();
+        let mut state = ReversiState::initial_state();
+
+        for (i, &action) in parse_move_sequence("f5d6c3d3c4f4f6f3e6e7")
+            .unwrap()
+            .iter()
+            .enumerate()
+        {
+            samples.push(evaluator.sample(&state, state.current_player_turn(), i as f32 - 5.0));
+            state.apply_move(action);
+        }
+
+        let before = evaluator.mean_squared_error(&samples);
+        let after = evaluator.train(&samples, 20, 0.2);
+
+        assert!(
+            after < before / 10.0,
+            "error went from {} to {}",
+            before,
+            after
+        );
+    }
+
+    #[test]
+    fn weights_expect_round_trip() {
+        let mut evaluator = PatternEvaluator::standard(3);
+        let state = ReversiState::initial_state();
+        let sample = evaluator.sample(&state, PlayerColor::Black, -2.5);
+        evaluator.train(&[sample], 5, 0.5);
+
+        let mut bytes = Vec::new();
+        evaluator.write_to(&mut bytes).unwrap();
+        let loaded = PatternEvaluator::read_from(bytes.as_slice(), 8).unwrap();
+
+        assert_eq!(evaluator, loaded);
+        assert_eq!(
+            evaluator.evaluate(&state, PlayerColor::Black),
+            loaded.evaluate(&state, PlayerColor::Black)
+        );
+
+        assert!(PatternEvaluator::read_from(&bytes[..bytes.len() - 1], 8).is_err());
+        assert!(PatternEvaluator::read_from(&b"nope"[..], 8).is_err());
+
+        // Weights for the 8x8 board can't evaluate any other size.
+        assert!(PatternEvaluator::read_from(bytes.as_slice(), 6).is_err());
+    }
+
+    #[test]
+    fn new_expects_errors_on_bad_patterns() {
+        let off_board = Pattern::new(vec![BoardPosition::new(8, 0)]);
+        let repeated = Pattern::from_notation("a1 a1", 8).unwrap();
+
+        assert!(PatternEvaluator::new(8, vec![off_board], 1).is_err());
+        assert!(PatternEvaluator::new(8, vec![repeated], 1).is_err());
+        assert!(PatternEvaluator::new(8, vec![Pattern::new(vec![])], 1).is_err());
+        assert!(PatternEvaluator::new(8, standard_patterns(), 0).is_err());
+        assert!(PatternEvaluator::new(8, standard_patterns(), MAX_PHASES + 1).is_err());
+        assert!(PatternEvaluator::new(0, standard_patterns(), 1).is_err());
+        assert!(PatternEvaluator::new(MAX_BOARD_SIZE + 1, standard_patterns(), 1).is_err());
+
+        let large = Pattern::from_notation("a1 b1 c1 d1 e1 f1 g1 h1 a2 b2 c2 d2", 8).unwrap();
+        assert!(PatternEvaluator::new(8, vec![large; MAX_PATTERNS], MAX_PHASES).is_err());
+        assert!(PatternEvaluator::new(8, standard_patterns(), MAX_PHASES).is_ok());
+    }
+
+    #[test]
+    fn read_from_expects_errors_on_oversized_header() {
+        let mut bytes = Vec::new();
+        PatternEvaluator::standard(1).write_to(&mut bytes).unwrap();
+
+        // The header is the magic and version, then the board size, phases and pattern count.
+        let corrupt = |offset: usize, value: &[u8]| {
+            let mut corrupted = bytes.clone();
+            corrupted[offset..offset + value.len()].copy_from_slice(value);
+            PatternEvaluator::read_from(corrupted.as_slice(), 8)
+        };
+
+        assert!(corrupt(8, &[200]).is_err());
+        assert!(corrupt(9, &u32::MAX.to_le_bytes()).is_err());
+        assert!(corrupt(13, &u32::MAX.to_le_bytes()).is_err());
+        assert!(corrupt(8, &[8]).is_ok());
+    }
+
+    #[test]
+    fn label_positions_expects_final_margin_for_player_to_move() {
+        // Play out a game by always taking the first legal move.
+        let mut state = ReversiState::initial_state();
+        let mut moves = Vec::new();
+
+        while !state.is_game_over() {
+            let action = state.legal_moves()[0];
+            moves.push(action);
+            state.apply_move(action);
+        }
+
+        let final_margin = margin(&state, PlayerColor::Black);
+        let labelled = label_positions(&moves).unwrap();
+
+        assert_eq!(moves.len(), labelled.len());
+        assert_eq!(
+            ReversiState::initial_state().to_string(),
+            labelled[0].0.to_string()
+        );
+
+        for (_, player, label) in &labelled {
+            match player {
+                PlayerColor::Black => assert_eq!(final_margin, *label),
+                PlayerColor::White => assert_eq!(-final_margin, *label),
+            }
+        }
+
+        assert!(label_positions(&moves[..moves.len() - 1]).is_none());
+        assert!(label_positions(&parse_move_sequence::<8>("a1").unwrap()).is_none());
+    }
+}
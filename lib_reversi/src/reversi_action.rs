@@ -1,28 +1,33 @@
-use crate::BoardPosition;
+use crate::{BoardPosition, NotationError, BOARD_SIZE};
 use lib_boardgame::GameAction;
-use lib_printer::{out, out_impl};
 use std::fmt;
 
+/// A move in Reversi played on an NxN board.
 #[derive(Copy, Clone, PartialEq)]
-pub enum ReversiPlayerAction {
+pub enum SizedReversiAction<const N: usize> {
     PassTurn,
     Move { position: BoardPosition },
 }
 
-impl GameAction for ReversiPlayerAction {
+/// A move in Reversi played on the standard 8x8 board.
+pub type ReversiPlayerAction = SizedReversiAction<BOARD_SIZE>;
+
+impl<const N: usize> GameAction for SizedReversiAction<N> {
     fn is_forced_pass(self) -> bool {
-        match self {
-            ReversiPlayerAction::PassTurn => true,
-            _ => false,
-        }
+        matches!(self, SizedReversiAction::PassTurn)
+    }
+
+    /// Othello notation for the corners of the board, e.g. "a1-h8 or pass".
+    fn input_hint() -> String {
+        format!("a1-{}{} or pass", (b'a' + N as u8 - 1) as char, N)
     }
 }
 
-impl fmt::Debug for ReversiPlayerAction {
+impl<const N: usize> fmt::Debug for SizedReversiAction<N> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match self {
-            ReversiPlayerAction::PassTurn => "(player passes turn)".to_owned(),
-            ReversiPlayerAction::Move { position } => {
+            SizedReversiAction::PassTurn => "(player passes turn)".to_owned(),
+            SizedReversiAction::Move { position } => {
                 format!("({}, {})", position.col(), position.row())
             }
         };
@@ -30,49 +35,26 @@ impl fmt::Debug for ReversiPlayerAction {
         write!(f, "{}", msg)
     }
 }
-impl fmt::Display for ReversiPlayerAction {
+
+/// Displays the action in Othello notation, e.g. "f5", or "pass".
+impl<const N: usize> fmt::Display for SizedReversiAction<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            SizedReversiAction::PassTurn => write!(f, "pass"),
+            SizedReversiAction::Move { position } => write!(f, "{}", position.to_notation(N)),
+        }
     }
 }
 
-impl std::str::FromStr for ReversiPlayerAction {
-    type Err = usize;
+/// Parses an action in Othello notation, e.g. "f5" or "F5", or "pass".
+impl<const N: usize> std::str::FromStr for SizedReversiAction<N> {
+    type Err = NotationError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.to_lowercase() == "pass" {
-            return Ok(ReversiPlayerAction::PassTurn);
-        }
-
-        let nums: Vec<_> = s.split(',').map(|x| x.trim()).collect();
-
-        if nums.len() != 2 {
-            out!("Invalid input: {} -- expected format: col,row", s);
-            return Err(9);
-        }
-
-        let col = nums[0].parse::<usize>();
-        let row = nums[1].parse::<usize>();
-
-        if let (Ok(col), Ok(row)) = (col, row) {
-            let position = BoardPosition::new(col, row);
-            if col > crate::reversi_gamestate::ReversiState::BOARD_SIZE
-                || row >= crate::reversi_gamestate::ReversiState::BOARD_SIZE
-            {
-                out!(
-                    "Position out of bounds of board. Input: {:?}, actual board size: {}",
-                    position,
-                    crate::reversi_gamestate::ReversiState::BOARD_SIZE
-                );
-
-                Err(9)
-            } else {
-                let action = ReversiPlayerAction::Move { position };
-                Ok(action)
-            }
-        } else {
-            out!("Didn't recognize input as a board position: {}", s);
-            Err(9)
+        match s.trim().to_lowercase().as_str() {
+            "pass" | "pa" => Ok(SizedReversiAction::PassTurn),
+            _ => BoardPosition::from_notation(s, N)
+                .map(|position| SizedReversiAction::Move { position }),
         }
     }
 }
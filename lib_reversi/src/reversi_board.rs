@@ -1,10 +1,14 @@
 use lib_boardgame::PlayerColor;
 
-/// The size of the board.
+/// The size of the standard board.
 /// E.x., if this is 8, the Reversi board is 8x8 spaces large.
-pub(crate) const BOARD_SIZE: usize = 8;
+pub const BOARD_SIZE: usize = 8;
 
-pub(crate) type Board = [[Option<ReversiPiece>; BOARD_SIZE]; BOARD_SIZE];
+/// The pieces on an NxN board, indexed by [row][col], with the top row first.
+pub type SizedBoard<const N: usize> = [[Option<ReversiPiece>; N]; N];
+
+/// The pieces on the standard board, indexed by [row][col], with the top row first.
+pub type Board = SizedBoard<BOARD_SIZE>;
 
 /// When traversing pieces on the board,
 /// a positive direction indicates increasing values for col or row,
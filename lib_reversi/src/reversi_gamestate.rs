@@ -1,15 +1,24 @@
 use crate::util::{opponent, BoardDirectionIter};
 use crate::{
-    reversi_board::board_directions, Board, BoardPosition, Directions, ReversiPiece,
-    ReversiPlayerAction, BOARD_SIZE,
+    reversi_board::board_directions, BoardPosition, Directions, PassRule, ReversiPiece,
+    ReversiVariant, Scoring, SizedBoard, SizedReversiAction, StableDiscs, StartingPosition,
+    BOARD_SIZE,
 };
-use lib_boardgame::{GameState, PlayerColor};
+use lib_boardgame::{
+    split_position_text, GameResult, GameState, IllegalMoveError, IllegalMoveReason, Mobility,
+    MoveOrdering, PlayerColor, PositionError, PositionSetup, StateEncoding, UndoMove,
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use std::fmt;
 
+/// The state of a game of Reversi played on an NxN board,
+/// where N is even and between 4 and 26.
 #[derive(Clone)]
-pub struct ReversiState {
+pub struct SizedReversiState<const N: usize> {
     /// The underlying 2d array of board pieces.
-    board: Board,
+    board: SizedBoard<N>,
 
     /// The player whose turn it currently is.
     current_player_turn: PlayerColor,
@@ -20,53 +29,179 @@ pub struct ReversiState {
     /// The count of black pieces on the board.
     black_pieces_count: usize,
 
-    cur_state_legal_moves: Vec<ReversiPlayerAction>,
+    cur_state_legal_moves: Vec<SizedReversiAction<N>>,
 
     is_game_over: bool,
+
+    /// The rules this game is played by.
+    variant: ReversiVariant,
+}
+
+/// How human_friendly_with_stable_discs() draws stable discs.
+const STABLE_BLACK_PIECE: char = '#';
+const STABLE_WHITE_PIECE: char = '@';
+
+/// The state of a game of Reversi played on the standard 8x8 board.
+pub type ReversiState = SizedReversiState<BOARD_SIZE>;
+
+impl<const N: usize> Default for SizedReversiState<N> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl ReversiState {
-    pub const BOARD_SIZE: usize = BOARD_SIZE;
+impl<const N: usize> SizedReversiState<N> {
+    pub const BOARD_SIZE: usize = N;
 
     pub fn new() -> Self {
-        let board: Board = [[None; BOARD_SIZE]; BOARD_SIZE];
+        assert!(
+            (4..=26).contains(&N) && N.is_multiple_of(2),
+            "Reversi boards must have an even size between 4 and 26, not {}",
+            N
+        );
+
+        let board: SizedBoard<N> = [[None; N]; N];
 
-        ReversiState {
+        SizedReversiState {
             board,
             current_player_turn: PlayerColor::Black,
             white_pieces_count: 0,
             black_pieces_count: 0,
             cur_state_legal_moves: Vec::new(),
             is_game_over: false,
+            variant: ReversiVariant::default(),
         }
     }
 
-    pub fn from_board(board: Board) -> Self {
-        let mut state = ReversiState::new();
+    /// Returns a fresh, ready-to-play game state played by the given variant's rules.
+    pub fn with_variant(variant: ReversiVariant) -> Self {
+        let mut state = Self::new();
+        state.variant = variant;
+        state.initialize_board();
+
+        state
+    }
+
+    /// The rules this game is played by.
+    pub fn variant(&self) -> ReversiVariant {
+        self.variant
+    }
+
+    /// Continues the game from this position by the given variant's scoring and passing rules.
+    /// (The variant's starting position is ignored, since the game has already started.)
+    pub fn set_rules(&mut self, variant: ReversiVariant) {
+        self.variant = ReversiVariant {
+            start: self.variant.start,
+            ..variant
+        };
+
+        self.update_stored_state_values();
+    }
+
+    /// Like human_friendly(), but marks stable discs (see stable_discs()),
+    /// drawing them as '#' for Black and '@' for White.
+    pub fn human_friendly_with_stable_discs(&self) -> String {
+        self.render(Some(&self.stable_discs()))
+    }
+
+    /// Draws the board for human_friendly(), marking the given stable discs (if any).
+    fn render(&self, stable: Option<&StableDiscs<N>>) -> String {
+        let mut result = String::new();
+
+        const BLACK_PIECE: char = 'X';
+        const WHITE_PIECE: char = 'O';
+        const EMPTY_SPACE: char = '-';
+
+        result.reserve(Self::friendly_print_size());
+
+        result.push('\n');
+
+        let label = Self::row_label_width();
+
+        for row in (0..N).rev() {
+            result.push_str(&format!("{:>width$}| ", N - row, width = label));
+
+            for col in 0..N {
+                let position = BoardPosition::new(col, row);
+                let is_stable = stable.is_some_and(|s| s.contains(position));
+
+                let piece_char = match (self.get_piece(position), is_stable) {
+                    (Some(ReversiPiece::White), false) => WHITE_PIECE,
+                    (Some(ReversiPiece::Black), false) => BLACK_PIECE,
+                    (Some(ReversiPiece::White), true) => STABLE_WHITE_PIECE,
+                    (Some(ReversiPiece::Black), true) => STABLE_BLACK_PIECE,
+                    (None, _) => EMPTY_SPACE,
+                };
+
+                result.push(piece_char);
+                result.push(' ');
+            }
+
+            result.push('\n');
+        }
+
+        result.push_str(&" ".repeat(label + 1));
+        for _ in 0..N {
+            result.push_str("--");
+        }
+
+        result.push('\n');
+        result.push_str(&" ".repeat(label + 2));
+        for col in 0..N {
+            result.push_str(&format!("{} ", (b'a' + col as u8) as char));
+        }
+
+        result
+    }
+
+    /// The count of discs of the given player's color on the board.
+    pub fn disc_count(&self, player: PlayerColor) -> usize {
+        match player {
+            PlayerColor::White => self.white_pieces_count(),
+            PlayerColor::Black => self.black_pieces_count(),
+        }
+    }
+
+    /// Creates a state with the given board, with Black to move.
+    /// The board is indexed by [row][col], with the top row (NBoard's row 1) first.
+    pub fn from_board(board: SizedBoard<N>) -> Self {
+        let mut state = Self::new();
 
-        state.board = board;
+        state.set_board(board);
+        state.update_stored_state_values();
 
         state
     }
 
+    fn set_board(&mut self, board: SizedBoard<N>) {
+        for (row_p, row) in board.iter().enumerate() {
+            for (col, piece) in row.iter().enumerate() {
+                let position = BoardPosition::new(col, N - row_p - 1);
+                self.set_piece(position, *piece);
+            }
+        }
+    }
+
+    /// Parses a board in the form given by human_friendly(), with Black to move.
+    /// Stable discs marked by human_friendly_with_stable_discs() are read as ordinary discs.
     pub fn from_board_str(board_str: &str) -> Self {
-        let mut state = ReversiState::new();
+        let mut state = Self::new();
 
         for (i_line, line) in board_str
             .lines()
             .map(|l| l.trim())
-            .filter(|l| l.len() > 0)
+            .filter(|l| !l.is_empty())
             .enumerate()
         {
             let mut char_index = 0;
 
             for c in line.chars() {
                 let piece = match c {
-                    'X' => {
+                    'X' | STABLE_BLACK_PIECE => {
                         char_index += 1;
                         Some(ReversiPiece::Black)
                     }
-                    'O' => {
+                    'O' | STABLE_WHITE_PIECE => {
                         char_index += 1;
                         Some(ReversiPiece::White)
                     }
@@ -78,25 +213,27 @@ impl ReversiState {
                 };
 
                 if let Some(piece) = piece {
-                    let i_line = (BOARD_SIZE - 1) - i_line;
+                    let i_line = (N - 1) - i_line;
                     let position = BoardPosition::new(char_index - 1, i_line);
                     state.set_piece(position, Some(piece))
                 }
             }
         }
 
+        state.update_stored_state_values();
+
         state
     }
 
     fn transform_coords(position: BoardPosition) -> (usize, usize) {
-        (position.col, BOARD_SIZE - position.row - 1)
+        (position.col, N - position.row - 1)
     }
 
     /// Given an (x,y) coord within range of the board, return the ReversiPiece
     /// present on that spot, or None if the position is empty.
     /// Note: (0,0) is the bottom-left position.
     pub(super) fn get_piece(&self, position: BoardPosition) -> Option<ReversiPiece> {
-        let (col_p, row_p) = ReversiState::transform_coords(position);
+        let (col_p, row_p) = Self::transform_coords(position);
 
         self.board[row_p][col_p]
     }
@@ -113,7 +250,7 @@ impl ReversiState {
 
     /// Set the piece at the coordinates to the given piece.
     fn set_piece(&mut self, position: BoardPosition, piece: Option<ReversiPiece>) {
-        let (col_p, row_p) = ReversiState::transform_coords(position);
+        let (col_p, row_p) = Self::transform_coords(position);
 
         let existing = self.board[row_p][col_p];
 
@@ -147,18 +284,31 @@ impl ReversiState {
     /// might as well pre-compute it so we can reserve the space ahead of time.
     /// (A test exists to confirm this is accurate.)
     const fn friendly_print_size() -> usize {
-        199
+        let label = Self::row_label_width();
+
+        // A leading newline, N rows of "{label}| X X ...\n",
+        // the dashed line and its newline, then the column letters.
+        1 + N * (label + 2 + 2 * N + 1) + (label + 1 + 2 * N) + 1 + (label + 2 + 2 * N)
+    }
+
+    /// The width of the row numbers labelling the human-friendly output.
+    const fn row_label_width() -> usize {
+        if N >= 10 {
+            2
+        } else {
+            1
+        }
     }
 
     fn within_board_bounds(position: BoardPosition) -> bool {
-        position.col < BOARD_SIZE && position.row < BOARD_SIZE
+        position.col < N && position.row < N
     }
 
     fn traverse_from(
         origin: BoardPosition,
         direction: Directions,
     ) -> impl Iterator<Item = BoardPosition> {
-        BoardDirectionIter::new(origin, direction)
+        BoardDirectionIter::new(origin, direction, N)
     }
 
     /// Given a position of a piece on the board,
@@ -190,7 +340,7 @@ impl ReversiState {
         direction: Directions,
     ) -> Option<BoardPosition> {
         // Start by walking across every piece in the given direction...
-        for (index, position) in ReversiState::traverse_from(origin, direction).enumerate() {
+        for (index, position) in Self::traverse_from(origin, direction).enumerate() {
             let piece = self.get_piece(position);
 
             match piece {
@@ -218,7 +368,7 @@ impl ReversiState {
     }
 
     /// Returns the possible moves the given player can make for the current state.
-    fn calc_legal_moves(&self, player: PlayerColor) -> Vec<ReversiPlayerAction> {
+    fn calc_legal_moves(&self, player: PlayerColor) -> Vec<SizedReversiAction<N>> {
         let piece_color = match player {
             PlayerColor::Black => ReversiPiece::Black,
             PlayerColor::White => ReversiPiece::White,
@@ -231,8 +381,8 @@ impl ReversiState {
         ];
 
         // (0,0), (0,1) ... (4, 7), (5, 0) ... (7, 7)
-        let all_positions = (0..(Self::BOARD_SIZE * Self::BOARD_SIZE))
-            .map(|index| ((index / Self::BOARD_SIZE), (index % Self::BOARD_SIZE)))
+        let all_positions = (0..(N * N))
+            .map(|index| ((index / N), (index % N)))
             .map(|(col, row)| BoardPosition::new(col, row));
 
         let empty_positions = all_positions.filter(|&pos| self.get_piece(pos).is_none());
@@ -260,14 +410,14 @@ impl ReversiState {
 
                 false
             })
-            .map(|position| ReversiPlayerAction::Move { position })
+            .map(|position| SizedReversiAction::Move { position })
             .for_each(|a| moves.push(a));
         // Note on above line: I have tried using "extend()" instead of "for_each()", but that incurred
         // a performance hit for reasons I don't understand.
 
         if moves.is_empty() {
             // There's always at least one legal choice: pass the turn
-            moves.push(ReversiPlayerAction::PassTurn);
+            moves.push(SizedReversiAction::PassTurn);
         }
 
         moves
@@ -276,17 +426,15 @@ impl ReversiState {
     /// True if the the game has ended, either due to a forced win,
     /// draw, or forfeit.
     fn calc_is_game_over(&self) -> bool {
-        if self.white_pieces_count() + self.black_pieces_count()
-            == ReversiState::BOARD_SIZE * ReversiState::BOARD_SIZE
-        {
+        if self.white_pieces_count() + self.black_pieces_count() == N * N {
             // if the board is full, no player has a legal move by definition, so the game is over.
             return true;
         }
 
-        let cur_player_legal_moves = self.legal_moves(self.current_player_turn());
+        let cur_player_legal_moves = self.legal_moves();
 
         if cur_player_legal_moves.len() > 1
-            || cur_player_legal_moves[0] != ReversiPlayerAction::PassTurn
+            || cur_player_legal_moves[0] != SizedReversiAction::PassTurn
         {
             return false;
         }
@@ -294,8 +442,7 @@ impl ReversiState {
         let opponent_color = opponent(self.current_player_turn());
         let opponent_legal_moves = self.calc_legal_moves(opponent_color);
 
-        if opponent_legal_moves.len() > 1
-            || opponent_legal_moves[0] != ReversiPlayerAction::PassTurn
+        if opponent_legal_moves.len() > 1 || opponent_legal_moves[0] != SizedReversiAction::PassTurn
         {
             return false;
         }
@@ -303,87 +450,16 @@ impl ReversiState {
         true
     }
 
-    fn update_stored_state_values(&mut self) {
-        self.cur_state_legal_moves = self.calc_legal_moves(self.current_player_turn);
-        self.is_game_over = self.calc_is_game_over();
-    }
-}
-
-impl GameState for ReversiState {
-    type Action = ReversiPlayerAction;
-
-    /// Returns a human-friendly string for representing the state.
-    fn human_friendly(&self) -> String {
-        let mut result = String::new();
-
-        const BLACK_PIECE: char = 'X';
-        const WHITE_PIECE: char = 'O';
-        const EMPTY_SPACE: char = '-';
-
-        result.reserve(ReversiState::friendly_print_size());
-
-        result.push('\n');
-
-        for row in (0..BOARD_SIZE).rev() {
-            result.push_str(&format!("{}| ", row));
-
-            for col in 0..BOARD_SIZE {
-                let piece = self.get_piece(BoardPosition::new(col, row));
-
-                let piece_char = match piece {
-                    Some(ReversiPiece::White) => WHITE_PIECE,
-                    Some(ReversiPiece::Black) => BLACK_PIECE,
-                    None => EMPTY_SPACE,
-                };
-
-                result.push(piece_char);
-                result.push(' ');
-            }
-
-            result.push('\n');
-        }
-
-        result.push_str("  ");
-        for _ in 0..BOARD_SIZE {
-            result.push_str("--");
-        }
-
-        result.push('\n');
-        result.push_str("   ");
-        for col in 0..BOARD_SIZE {
-            result.push_str(&format!("{} ", col));
-        }
-
-        result
-    }
-
-    // TODO: update trait if we're not using PlayerColor anymore
-    fn legal_moves(&self, _player: PlayerColor) -> &[Self::Action] {
-        assert_eq!(_player, self.current_player_turn(), "Until the trait is updated, the requested legal moves must always be for the current player.");
-        self.cur_state_legal_moves.as_slice()
-    }
-
-    /// Apply the given move (or 'action') to this state, mutating this state
-    /// and advancing it to the resulting state.
-    /// In the game of Reversi, this places the piece at the given position,
-    /// and flips all opponent pieces in any direction that terminates with another one of our pieces.
-    ///
-    /// Example:
-    ///    O X X X *
-    ///          X X
-    ///        O   X
-    ///            X
-    ///             
-    /// Placing a white piece ('O') at the position marked with the * will result in the following state:
-    ///
-    ///    O O O O O
-    ///          O X
-    ///        O   X
-    ///            X
-    fn apply_move(&mut self, action: Self::Action) {
+    /// Applies the move, as described by apply_move(),
+    /// and pushes the position of every flipped piece to the given vec, if any.
+    fn apply_move_recording_flips(
+        &mut self,
+        action: SizedReversiAction<N>,
+        mut flipped: Option<&mut Vec<BoardPosition>>,
+    ) {
         let position = match action {
-            ReversiPlayerAction::Move { position } => position,
-            ReversiPlayerAction::PassTurn => {
+            SizedReversiAction::Move { position } => position,
+            SizedReversiAction::PassTurn => {
                 // Passing a turn implies giving control to the other player, and doing nothing else.
                 self.current_player_turn = opponent(self.current_player_turn);
                 self.update_stored_state_values();
@@ -391,7 +467,7 @@ impl GameState for ReversiState {
             }
         };
 
-        if !ReversiState::within_board_bounds(position) {
+        if !Self::within_board_bounds(position) {
             panic!("Provided position exceeds bounds: {:?}", position);
         }
 
@@ -428,10 +504,14 @@ impl GameState for ReversiState {
                 let sibling = self.find_sibling_piece_pos(origin, player_piece, direction);
 
                 if let Some(sibling) = sibling {
-                    ReversiState::traverse_from(origin, direction)
+                    Self::traverse_from(origin, direction)
                         .take_while(|&p| p != sibling)
                         .for_each(|p| {
                             self.flip_piece(p);
+
+                            if let Some(flipped) = flipped.as_mut() {
+                                flipped.push(p);
+                            }
                         });
                 }
             }
@@ -442,16 +522,121 @@ impl GameState for ReversiState {
         self.update_stored_state_values();
     }
 
+    fn update_stored_state_values(&mut self) {
+        self.cur_state_legal_moves = self.calc_legal_moves(self.current_player_turn);
+        self.is_game_over = self.calc_is_game_over();
+
+        // Once the game is over, not even passing is legal.
+        if self.is_game_over {
+            self.cur_state_legal_moves.clear();
+            return;
+        }
+
+        // Under auto-pass rules, a player with no moves never has to choose to pass.
+        if self.variant.passing == PassRule::AutoPass
+            && self.cur_state_legal_moves[0] == SizedReversiAction::PassTurn
+        {
+            self.current_player_turn = opponent(self.current_player_turn);
+            self.cur_state_legal_moves = self.calc_legal_moves(self.current_player_turn);
+        }
+    }
+}
+
+impl<const N: usize> GameState for SizedReversiState<N> {
+    type Action = SizedReversiAction<N>;
+
+    /// Returns a human-friendly string for representing the state.
+    fn human_friendly(&self) -> String {
+        self.render(None)
+    }
+
+    fn legal_moves(&self) -> &[Self::Action] {
+        self.cur_state_legal_moves.as_slice()
+    }
+
+    /// Apply the given move (or 'action') to this state, mutating this state
+    /// and advancing it to the resulting state.
+    /// In the game of Reversi, this places the piece at the given position,
+    /// and flips all opponent pieces in any direction that terminates with another one of our pieces.
+    ///
+    /// Example:
+    ///    O X X X *
+    ///          X X
+    ///        O   X
+    ///            X
+    ///             
+    /// Placing a white piece ('O') at the position marked with the * will result in the following state:
+    ///
+    ///    O O O O O
+    ///          O X
+    ///        O   X
+    ///            X
+    fn apply_move(&mut self, action: Self::Action) {
+        self.apply_move_recording_flips(action, None);
+    }
+
+    fn try_apply_move(&mut self, action: Self::Action) -> Result<(), IllegalMoveError> {
+        let illegal = |reason| Err(IllegalMoveError::new(action, reason));
+
+        if self.is_game_over() {
+            return illegal(IllegalMoveReason::GameOver);
+        }
+
+        if let SizedReversiAction::Move { position } = action {
+            if !Self::within_board_bounds(position) {
+                return illegal(IllegalMoveReason::OutOfBounds);
+            }
+
+            if self.get_piece(position).is_some() {
+                return illegal(IllegalMoveReason::Occupied);
+            }
+        }
+
+        // A move must flip at least one piece, and passing is only legal with no other option.
+        if !self.cur_state_legal_moves.contains(&action) {
+            return illegal(IllegalMoveReason::NotLegal);
+        }
+
+        self.apply_move(action);
+
+        Ok(())
+    }
+
     /// Returns the current player whose turn it currently is.
     fn current_player_turn(&self) -> PlayerColor {
         self.current_player_turn
     }
 
-    /// Returns the score of the given player in this state.
+    /// Returns the score of the given player in this state: their disc count,
+    /// or under anti-Reversi scoring, their opponent's disc count
+    /// (so that a higher score is always better).
     fn player_score(&self, player: PlayerColor) -> usize {
-        match player {
-            PlayerColor::White => self.white_pieces_count(),
-            PlayerColor::Black => self.black_pieces_count(),
+        match self.variant.scoring {
+            Scoring::MostDiscs => self.disc_count(player),
+            Scoring::FewestDiscs => self.disc_count(player.opponent()),
+        }
+    }
+
+    /// The winner is decided by the variant's scoring rule.
+    fn game_result(&self) -> Option<GameResult> {
+        if !self.is_game_over() {
+            return None;
+        }
+
+        let black = self.disc_count(PlayerColor::Black);
+        let white = self.disc_count(PlayerColor::White);
+
+        let black_wins = match self.variant.scoring {
+            Scoring::MostDiscs => black > white,
+            Scoring::FewestDiscs => black < white,
+        };
+
+        if black == white {
+            Some(GameResult::Tie)
+        } else if black_wins {
+            Some(GameResult::BlackWins)
+        } else {
+            Some(GameResult::WhiteWins)
         }
     }
 
@@ -463,13 +648,34 @@ impl GameState for ReversiState {
     }
 
     fn initialize_board(&mut self) {
-        self.set_piece(BoardPosition::new(3, 4), Some(ReversiPiece::White));
-        self.set_piece(BoardPosition::new(4, 4), Some(ReversiPiece::Black));
+        let (low, high) = (N / 2 - 1, N / 2);
+
+        // The top row is the same in every start; the parallel start swaps the bottom row.
+        let (bottom_left, bottom_right) = match self.variant.start {
+            StartingPosition::Parallel => (ReversiPiece::White, ReversiPiece::Black),
+            _ => (ReversiPiece::Black, ReversiPiece::White),
+        };
+
+        self.set_piece(BoardPosition::new(low, high), Some(ReversiPiece::White));
+        self.set_piece(BoardPosition::new(high, high), Some(ReversiPiece::Black));
 
-        self.set_piece(BoardPosition::new(3, 3), Some(ReversiPiece::Black));
-        self.set_piece(BoardPosition::new(4, 3), Some(ReversiPiece::White));
+        self.set_piece(BoardPosition::new(low, low), Some(bottom_left));
+        self.set_piece(BoardPosition::new(high, low), Some(bottom_right));
 
         self.update_stored_state_values();
+
+        if let StartingPosition::RandomOpening { moves, seed } = self.variant.start {
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            for _ in 0..moves {
+                if self.is_game_over() {
+                    break;
+                }
+
+                let action = *self.cur_state_legal_moves.choose(&mut rng).unwrap();
+                self.apply_move(action);
+            }
+        }
     }
 
     fn skip_turn(&mut self) {
@@ -484,7 +690,231 @@ impl GameState for ReversiState {
     }
 }
 
-impl fmt::Display for ReversiState {
+/// The record of a move applied to a ReversiState, used to undo it.
+#[derive(Clone, Debug)]
+pub struct ReversiUndo<const N: usize = BOARD_SIZE> {
+    action: SizedReversiAction<N>,
+    flipped: Vec<BoardPosition>,
+    prior_legal_moves: Vec<SizedReversiAction<N>>,
+    prior_is_game_over: bool,
+
+    /// Under auto-pass rules, the same player may move again, so the turn is recorded too.
+    prior_player_turn: PlayerColor,
+}
+
+impl<const N: usize> UndoMove for SizedReversiState<N> {
+    type Undo = ReversiUndo<N>;
+
+    fn make_move(&mut self, action: SizedReversiAction<N>) -> ReversiUndo<N> {
+        let prior_legal_moves = self.cur_state_legal_moves.clone();
+        let prior_is_game_over = self.is_game_over;
+        let prior_player_turn = self.current_player_turn;
+
+        let mut flipped = Vec::new();
+        self.apply_move_recording_flips(action, Some(&mut flipped));
+
+        ReversiUndo {
+            action,
+            flipped,
+            prior_legal_moves,
+            prior_is_game_over,
+            prior_player_turn,
+        }
+    }
+
+    fn undo_move(&mut self, undo: ReversiUndo<N>) {
+        if let SizedReversiAction::Move { position } = undo.action {
+            self.set_piece(position, None);
+
+            for &p in &undo.flipped {
+                self.flip_piece(p);
+            }
+        }
+
+        self.current_player_turn = undo.prior_player_turn;
+        self.cur_state_legal_moves = undo.prior_legal_moves;
+        self.is_game_over = undo.prior_is_game_over;
+    }
+}
+
+impl<const N: usize> Mobility for SizedReversiState<N> {
+    /// Like legal_moves(), empty for either player once the game is over.
+    fn legal_moves_for(&self, player: PlayerColor) -> Vec<SizedReversiAction<N>> {
+        if self.is_game_over {
+            Vec::new()
+        } else if player == self.current_player_turn() {
+            self.cur_state_legal_moves.clone()
+        } else {
+            self.calc_legal_moves(player)
+        }
+    }
+}
+
+impl<const N: usize> StateEncoding for SizedReversiState<N> {
+    /// Encodes the player to move, followed by one byte per board position
+    /// (0 for empty, 1 for black, 2 for white), row by row.
+    /// Games with non-standard scoring or passing rules append two bytes for those rules.
+    fn encode_state(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + N * N + 2);
+        bytes.push(self.current_player_turn.to_byte());
+
+        for row in 0..N {
+            for col in 0..N {
+                bytes.push(match self.get_piece(BoardPosition::new(col, row)) {
+                    None => 0,
+                    Some(ReversiPiece::Black) => 1,
+                    Some(ReversiPiece::White) => 2,
+                });
+            }
+        }
+
+        let rules = self.variant.encode_rules();
+        if rules != ReversiVariant::default().encode_rules() {
+            bytes.extend_from_slice(&rules);
+        }
+
+        bytes
+    }
+
+    fn decode_state(bytes: &[u8]) -> Option<Self> {
+        let (&turn, rest) = bytes.split_first()?;
+
+        let (cells, variant) = match rest.len() {
+            len if len == N * N => (rest, ReversiVariant::default()),
+            len if len == N * N + 2 => {
+                let (cells, rules) = rest.split_at(N * N);
+                (cells, ReversiVariant::decode_rules(rules)?)
+            }
+            _ => return None,
+        };
+
+        let mut state = Self::new();
+        state.current_player_turn = PlayerColor::from_byte(turn)?;
+        state.variant = variant;
+
+        for (i, &cell) in cells.iter().enumerate() {
+            let piece = match cell {
+                0 => None,
+                1 => Some(ReversiPiece::Black),
+                2 => Some(ReversiPiece::White),
+                _ => return None,
+            };
+
+            let position = BoardPosition::new(i % N, i / N);
+            state.set_piece(position, piece);
+        }
+
+        state.update_stored_state_values();
+
+        Some(state)
+    }
+}
+
+impl<const N: usize> PositionSetup for SizedReversiState<N> {
+    type Board = SizedBoard<N>;
+
+    /// Creates a state from a board indexed by [row][col], with the top row (NBoard's row 1) first.
+    /// The four center squares are occupied from the start of the game, so they can never be empty.
+    fn from_position(
+        board: SizedBoard<N>,
+        side_to_move: PlayerColor,
+    ) -> Result<Self, PositionError> {
+        let center = N / 2;
+
+        let (low, high) = (center - 1, center);
+
+        for &(row_p, col) in [(low, low), (low, high), (high, low), (high, high)].iter() {
+            if board[row_p][col].is_none() {
+                return Err(PositionError::Impossible(format!(
+                    "center square {} is empty",
+                    BoardPosition::new(col, N - row_p - 1).to_notation(N)
+                )));
+            }
+        }
+
+        let mut state = Self::new();
+        state.set_board(board);
+        state.current_player_turn = side_to_move;
+        state.update_stored_state_values();
+
+        Ok(state)
+    }
+
+    /// Parses the notation used by NBoard's BO[...] tag: N*N squares (64 on the standard board) from a1 onwards,
+    /// row by row, with '-' for empty, '*' (or 'X') for black, and 'O' for white,
+    /// followed by the player to move ('*' or 'O').
+    fn parse_position(text: &str) -> Result<Self, PositionError> {
+        let (squares, side_to_move) = split_position_text(text, N * N)?;
+
+        let mut board: SizedBoard<N> = [[None; N]; N];
+
+        for (i, c) in squares.into_iter().enumerate() {
+            board[i / N][i % N] = match c {
+                '-' | '.' => None,
+                '*' | 'X' | 'x' | 'B' | 'b' => Some(ReversiPiece::Black),
+                'O' | 'o' | 'W' | 'w' => Some(ReversiPiece::White),
+                _ => {
+                    return Err(PositionError::Malformed(format!(
+                        "unrecognized square: {:?}",
+                        c
+                    )))
+                }
+            };
+        }
+
+        Self::from_position(board, side_to_move)
+    }
+
+    fn position_string(&self) -> String {
+        let mut result = String::with_capacity(N * N + 2);
+
+        for row in self.board.iter() {
+            for piece in row.iter() {
+                result.push(match piece {
+                    None => '-',
+                    Some(ReversiPiece::Black) => '*',
+                    Some(ReversiPiece::White) => 'O',
+                });
+            }
+        }
+
+        result.push(' ');
+        result.push(match self.current_player_turn {
+            PlayerColor::Black => '*',
+            PlayerColor::White => 'O',
+        });
+
+        result
+    }
+}
+
+/// The classic positional weight of each square, by its distance from the nearest
+/// row edge and column edge (i.e. one quadrant of the standard board).
+/// Corners are the most valuable squares, while the squares
+/// adjacent to the corners tend to give them away.
+/// On larger boards, squares further from the edges are weighted as the center.
+const SQUARE_WEIGHTS: [[i8; 4]; 4] = [
+    [100, -20, 10, 5],
+    [-20, -50, -2, -2],
+    [10, -2, 1, 1],
+    [5, -2, 1, 0],
+];
+
+impl<const N: usize> MoveOrdering for SizedReversiState<N> {
+    /// Prioritizes moves by the positional weight of the square played.
+    fn move_priority(&self, action: SizedReversiAction<N>) -> f32 {
+        match action {
+            SizedReversiAction::PassTurn => 0.0,
+            SizedReversiAction::Move { position } => {
+                let edge_distance = |i: usize| usize::min(usize::min(i, N - 1 - i), 3);
+
+                SQUARE_WEIGHTS[edge_distance(position.row())][edge_distance(position.col())] as f32
+            }
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedReversiState<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.human_friendly())
     }
@@ -493,8 +923,13 @@ impl fmt::Display for ReversiState {
 #[cfg(test)]
 mod tests {
     use super::{
-        BoardPosition, GameState, PlayerColor, ReversiPiece, ReversiPlayerAction, ReversiState,
+        BoardPosition, GameState, IllegalMoveReason, Mobility, PlayerColor, PositionError,
+        PositionSetup, ReversiPiece, ReversiState, SizedReversiState, StateEncoding, UndoMove,
+    };
+    use crate::{
+        PassRule, ReversiPlayerAction, ReversiVariant, SizedReversiAction, StartingPosition,
     };
+    use lib_boardgame::GameResult;
 
     fn pos(col: usize, row: usize) -> BoardPosition {
         BoardPosition::new(col, row)
@@ -509,6 +944,187 @@ mod tests {
         assert_eq!(ReversiState::friendly_print_size(), stringified.len());
     }
 
+    #[test]
+    fn human_friendly_reserves_correct_size_for_other_board_sizes() {
+        let small = SizedReversiState::<6>::initial_state();
+        let large = SizedReversiState::<10>::initial_state();
+
+        assert_eq!(
+            SizedReversiState::<6>::friendly_print_size(),
+            small.human_friendly().len()
+        );
+        assert_eq!(
+            SizedReversiState::<10>::friendly_print_size(),
+            large.human_friendly().len()
+        );
+    }
+
+    #[test]
+    fn initial_state_expects_centered_pieces_on_small_board() {
+        let state = SizedReversiState::<6>::initial_state();
+
+        let expected = ["------", "------", "--O*--", "--*O--", "------", "------ *"].concat();
+
+        assert_eq!(expected, state.position_string());
+        let mut legal = state
+            .legal_moves()
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>();
+        legal.sort();
+
+        assert_eq!(vec!["b3", "c2", "d5", "e4"], legal);
+    }
+
+    /// Plays the first legal move every turn until the game ends,
+    /// then undoes every move, expecting to arrive back at the start.
+    fn play_out_and_undo<const N: usize>(variant: ReversiVariant) {
+        let initial = SizedReversiState::<N>::with_variant(variant);
+        let mut state = initial.clone();
+        let mut undos = Vec::new();
+
+        while !state.is_game_over() {
+            if variant.passing == PassRule::AutoPass {
+                assert!(!state.legal_moves().contains(&SizedReversiAction::PassTurn));
+            }
+
+            let action = state.legal_moves()[0];
+            undos.push(state.make_move(action));
+        }
+
+        let total = state.player_score(PlayerColor::Black) + state.player_score(PlayerColor::White);
+        assert!(total <= N * N);
+        assert!(undos.len() >= N * N - 4);
+
+        while let Some(undo) = undos.pop() {
+            state.undo_move(undo);
+        }
+
+        assert_eq!(initial.encode_state(), state.encode_state());
+        assert_eq!(initial.legal_moves(), state.legal_moves());
+    }
+
+    #[test]
+    fn games_play_to_the_end_on_every_board_size() {
+        play_out_and_undo::<4>(ReversiVariant::default());
+        play_out_and_undo::<6>(ReversiVariant::default());
+        play_out_and_undo::<8>(ReversiVariant::default());
+        play_out_and_undo::<10>(ReversiVariant::default());
+    }
+
+    #[test]
+    fn games_play_to_the_end_with_auto_pass() {
+        let auto_pass = ReversiVariant {
+            passing: PassRule::AutoPass,
+            ..Default::default()
+        };
+
+        play_out_and_undo::<4>(auto_pass);
+        play_out_and_undo::<6>(auto_pass);
+        play_out_and_undo::<8>(auto_pass);
+    }
+
+    #[test]
+    fn auto_pass_expects_turn_to_pass_to_player_with_moves() {
+        // Black has no move, but White can play b3 to flank b2.
+        let text = [
+            "*OOOOOOO",
+            "-*------",
+            "--------",
+            "---OO---",
+            "---OO---",
+            "--------",
+            "--------",
+            "-------- *",
+        ]
+        .concat();
+
+        let mut state = ReversiState::parse_position(&text).unwrap();
+        assert_eq!(&[ReversiPlayerAction::PassTurn], state.legal_moves());
+
+        state.set_rules(ReversiVariant {
+            passing: PassRule::AutoPass,
+            ..Default::default()
+        });
+
+        assert_eq!(PlayerColor::White, state.current_player_turn());
+        assert!(state.legal_moves().contains(&"b3".parse().unwrap()));
+        assert!(!state.legal_moves().contains(&ReversiPlayerAction::PassTurn));
+
+        let before = state.encode_state();
+        let undo = state.make_move("b3".parse().unwrap());
+        state.undo_move(undo);
+
+        assert_eq!(PlayerColor::White, state.current_player_turn());
+        assert_eq!(before, state.encode_state());
+    }
+
+    #[test]
+    fn anti_scoring_expects_fewest_discs_to_win() {
+        let text = [
+            "********",
+            "********",
+            "********",
+            "***OO***",
+            "***OO***",
+            "********",
+            "********",
+            "******** *",
+        ]
+        .concat();
+
+        let mut state = ReversiState::parse_position(&text).unwrap();
+        assert!(state.is_game_over());
+        assert_eq!(Some(GameResult::BlackWins), state.game_result());
+
+        state.set_rules(ReversiVariant::anti());
+
+        assert_eq!(Some(GameResult::WhiteWins), state.game_result());
+        assert_eq!(60, state.player_score(PlayerColor::White));
+        assert_eq!(4, state.player_score(PlayerColor::Black));
+        assert_eq!(4, state.disc_count(PlayerColor::White));
+
+        let decoded = ReversiState::decode_state(&state.encode_state()).unwrap();
+        assert_eq!(ReversiVariant::anti(), decoded.variant());
+        assert_eq!(Some(GameResult::WhiteWins), decoded.game_result());
+    }
+
+    #[test]
+    fn parallel_start_expects_side_by_side_discs() {
+        let state = ReversiState::with_variant(ReversiVariant {
+            start: StartingPosition::Parallel,
+            ..Default::default()
+        });
+
+        let mut legal = state
+            .legal_moves()
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>();
+        legal.sort();
+
+        assert_eq!(&state.position_string()[24..40], "---O*------O*---");
+        assert_eq!(vec!["c3", "c4", "c5", "c6"], legal);
+    }
+
+    #[test]
+    fn random_opening_expects_same_position_for_same_seed() {
+        let variant = ReversiVariant {
+            start: StartingPosition::RandomOpening { moves: 8, seed: 42 },
+            ..Default::default()
+        };
+
+        let first = ReversiState::with_variant(variant);
+        let second = ReversiState::with_variant(variant);
+
+        assert_eq!(first.position_string(), second.position_string());
+        assert_eq!(
+            12,
+            first.disc_count(PlayerColor::Black) + first.disc_count(PlayerColor::White)
+        );
+        assert_eq!(PlayerColor::Black, first.current_player_turn());
+    }
+
     #[test]
     fn state_can_set_and_get_piece() {
         let mut state = ReversiState::new();
@@ -636,12 +1252,98 @@ mod tests {
         assert_eq!(back_to_str, back_to_str_2);
     }
 
+    #[test]
+    fn decode_state_expects_round_trips_encoding() {
+        let mut state = ReversiState::initial_state();
+        let first_legal = state.legal_moves()[0];
+        state.apply_move(first_legal);
+
+        let decoded = ReversiState::decode_state(&state.encode_state()).unwrap();
+
+        assert_eq!(state.human_friendly(), decoded.human_friendly());
+        assert_eq!(state.current_player_turn(), decoded.current_player_turn());
+        assert_eq!(state.legal_moves(), decoded.legal_moves());
+
+        assert!(ReversiState::decode_state(&[0, 1, 2]).is_none());
+    }
+
+    #[test]
+    fn legal_moves_for_expects_moves_of_either_player() {
+        let mut state = ReversiState::initial_state();
+        state.apply_move(ReversiPlayerAction::Move {
+            position: pos(2, 4),
+        });
+
+        // White is on turn, so Black's moves must be computed.
+        assert_eq!(
+            state.legal_moves(),
+            state.legal_moves_for(PlayerColor::White)
+        );
+
+        let black_moves = state.legal_moves_for(PlayerColor::Black);
+        state.skip_turn();
+        assert_eq!(state.legal_moves(), black_moves.as_slice());
+    }
+
+    #[test]
+    fn try_apply_move_when_illegal_expects_error_and_unchanged_state() {
+        let mut state = ReversiState::initial_state();
+        let before = state.human_friendly();
+
+        let reason =
+            |state: &mut ReversiState, action| state.try_apply_move(action).unwrap_err().reason;
+        let at = |col, row| ReversiPlayerAction::Move {
+            position: pos(col, row),
+        };
+
+        assert_eq!(IllegalMoveReason::Occupied, reason(&mut state, at(3, 3)));
+        assert_eq!(IllegalMoveReason::OutOfBounds, reason(&mut state, at(8, 0)));
+        assert_eq!(IllegalMoveReason::NotLegal, reason(&mut state, at(0, 0)));
+        assert_eq!(
+            IllegalMoveReason::NotLegal,
+            reason(&mut state, ReversiPlayerAction::PassTurn)
+        );
+
+        assert_eq!(before, state.human_friendly());
+        assert_eq!(PlayerColor::Black, state.current_player_turn());
+
+        let legal = state.legal_moves()[0];
+        assert!(state.try_apply_move(legal).is_ok());
+        assert_eq!(PlayerColor::White, state.current_player_turn());
+    }
+
+    #[test]
+    fn undo_move_expects_restores_prior_states() {
+        let mut state = ReversiState::initial_state();
+        let mut snapshots = Vec::new();
+        let mut undos = Vec::new();
+
+        // Play the first legal move each turn, passing when forced to, until the game ends.
+        while !state.is_game_over() {
+            snapshots.push(state.clone());
+            let action = state.legal_moves()[0];
+            undos.push(state.make_move(action));
+        }
+
+        while let Some(undo) = undos.pop() {
+            state.undo_move(undo);
+            let expected = snapshots.pop().unwrap();
+
+            assert_eq!(expected.human_friendly(), state.human_friendly());
+            assert_eq!(expected.current_player_turn(), state.current_player_turn());
+            assert_eq!(expected.legal_moves(), state.legal_moves());
+            assert_eq!(expected.is_game_over(), state.is_game_over());
+            assert_eq!(expected.black_pieces_count(), state.black_pieces_count());
+            assert_eq!(expected.white_pieces_count(), state.white_pieces_count());
+        }
+    }
+
     #[test]
     fn cloning_state_is_deep_clone() {
         let state = ReversiState::initial_state();
         let mut cloned = state.clone();
 
-        let legal_moves = cloned.legal_moves(cloned.current_player_turn());
+        let legal_moves = cloned.legal_moves();
         let first_legal = legal_moves[0];
         cloned.apply_move(first_legal);
 
@@ -654,4 +1356,100 @@ mod tests {
             "The original state must not have been mutated when we mutated the cloned state."
         );
     }
+
+    const NBOARD_INITIAL: &str =
+        "---------------------------O*------*O--------------------------- *";
+
+    #[test]
+    fn parse_position_reads_nboard_initial_position() {
+        let parsed = ReversiState::parse_position(NBOARD_INITIAL).unwrap();
+        let initial = ReversiState::initial_state();
+
+        assert_eq!(initial.encode_state(), parsed.encode_state());
+        assert_eq!(initial.legal_moves(), parsed.legal_moves());
+        assert_eq!(NBOARD_INITIAL, initial.position_string());
+    }
+
+    #[test]
+    fn position_string_round_trips_with_white_to_move() {
+        let mut state = ReversiState::initial_state();
+        let action = state.legal_moves()[0];
+        state.apply_move(action);
+
+        let parsed = ReversiState::parse_position(&state.position_string()).unwrap();
+
+        assert_eq!(PlayerColor::White, parsed.current_player_turn());
+        assert_eq!(state.encode_state(), parsed.encode_state());
+        assert_eq!(state.legal_moves(), parsed.legal_moves());
+    }
+
+    #[test]
+    fn parse_position_rejects_bad_input() {
+        let too_short = &NBOARD_INITIAL[1..];
+        let bad_square = NBOARD_INITIAL.replacen('-', "?", 1);
+        let bad_side = NBOARD_INITIAL.replace(" *", " Z");
+        let empty_center = NBOARD_INITIAL.replacen('O', "-", 1);
+
+        assert!(matches!(
+            ReversiState::parse_position(too_short),
+            Err(PositionError::Malformed(_))
+        ));
+        assert!(matches!(
+            ReversiState::parse_position(&bad_square),
+            Err(PositionError::Malformed(_))
+        ));
+        assert!(matches!(
+            ReversiState::parse_position(&bad_side),
+            Err(PositionError::Malformed(_))
+        ));
+        assert!(matches!(
+            ReversiState::parse_position(&empty_center),
+            Err(PositionError::Impossible(_))
+        ));
+    }
+
+    #[test]
+    fn perft_expects_published_node_counts() {
+        let state = ReversiState::initial_state();
+        let expected = [4, 12, 56, 244, 1396, 8200];
+
+        for (depth, &count) in expected.iter().enumerate() {
+            assert_eq!(count, lib_boardgame::perft(&state, depth + 1));
+        }
+
+        let divide = lib_boardgame::perft_divide(&state, 3);
+        assert_eq!(4, divide.len());
+        assert!(divide.iter().all(|&(_, count)| count == 14));
+    }
+
+    #[test]
+    fn random_playouts_expect_conformance() {
+        use lib_boardgame::conformance::{
+            assert_mobility_conformance, check_mobility_conformance_from,
+        };
+
+        assert_mobility_conformance::<ReversiState>();
+        assert_mobility_conformance::<SizedReversiState<4>>();
+        assert_mobility_conformance::<SizedReversiState<6>>();
+
+        let variants = [
+            ReversiVariant::anti(),
+            ReversiVariant {
+                passing: PassRule::AutoPass,
+                ..Default::default()
+            },
+            ReversiVariant {
+                start: StartingPosition::Parallel,
+                ..Default::default()
+            },
+        ];
+
+        for &variant in variants.iter() {
+            let state = ReversiState::with_variant(variant);
+
+            if let Err(failure) = check_mobility_conformance_from(&state, Default::default()) {
+                panic!("{:?}: {}", variant, failure);
+            }
+        }
+    }
 }
@@ -0,0 +1,279 @@
+//! Stable discs: discs that can never be flipped, whatever is played for the rest of the game.
+//! A disc can only be flipped along one of the four lines through it (horizontal, vertical,
+//! and the two diagonals), so it's stable if it's safe along every one of them.
+//! It's safe along a line if the line is completely filled, or if it's next to the edge
+//! of the board or to a stable disc of its own color along that line.
+//! Starting from the corners, stability spreads along anchored edges and into the board,
+//! so repeating the check until nothing changes finds every disc this rule can prove stable.
+
+use crate::{BoardPosition, ReversiPiece, Scoring, SizedReversiState};
+use lib_boardgame::{GameResult, GameState, PlayerColor};
+
+/// The four lines through a square, as a step in one of their two directions.
+const AXES: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// The set of stable discs in a position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StableDiscs<const N: usize> {
+    /// Indexed by [row][col], as BoardPosition counts them.
+    stable: [[bool; N]; N],
+    black: usize,
+    white: usize,
+}
+
+impl<const N: usize> StableDiscs<N> {
+    /// True if there is a stable disc at the given position.
+    pub fn contains(&self, position: BoardPosition) -> bool {
+        self.stable[position.row][position.col]
+    }
+
+    /// The number of stable discs of the given player's color.
+    pub fn count(&self, player: PlayerColor) -> usize {
+        match player {
+            PlayerColor::Black => self.black,
+            PlayerColor::White => self.white,
+        }
+    }
+
+    /// The positions of every stable disc.
+    pub fn iter(&self) -> impl Iterator<Item = BoardPosition> + '_ {
+        (0..N)
+            .flat_map(|row| (0..N).map(move |col| BoardPosition::new(col, row)))
+            .filter(move |&p| self.contains(p))
+    }
+}
+
+fn step<const N: usize>(
+    position: BoardPosition,
+    (dc, dr): (isize, isize),
+) -> Option<BoardPosition> {
+    let col = position.col as isize + dc;
+    let row = position.row as isize + dr;
+
+    if col < 0 || row < 0 || col >= N as isize || row >= N as isize {
+        None
+    } else {
+        Some(BoardPosition::new(col as usize, row as usize))
+    }
+}
+
+impl<const N: usize> SizedReversiState<N> {
+    /// Finds the discs that can never be flipped (see the module documentation).
+    /// The set is conservative: every disc in it is stable, but not every stable disc may be found.
+    pub fn stable_discs(&self) -> StableDiscs<N> {
+        let positions = (0..N)
+            .flat_map(|row| (0..N).map(move |col| BoardPosition::new(col, row)))
+            .collect::<Vec<_>>();
+
+        let line_filled = |position: BoardPosition, (dc, dr): (isize, isize)| {
+            [(dc, dr), (-dc, -dr)].iter().all(|&direction| {
+                let mut current = position;
+
+                while let Some(next) = step::<N>(current, direction) {
+                    if self.get_piece(next).is_none() {
+                        return false;
+                    }
+
+                    current = next;
+                }
+
+                true
+            })
+        };
+
+        // Whether each occupied square's lines are filled only depends on the board,
+        // so it's worked out once, ahead of the spreading below.
+        let mut filled = [[[false; 4]; N]; N];
+        for &p in &positions {
+            if self.get_piece(p).is_some() {
+                for (axis, &direction) in AXES.iter().enumerate() {
+                    filled[p.row][p.col][axis] = line_filled(p, direction);
+                }
+            }
+        }
+
+        let mut stable = [[false; N]; N];
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for &p in &positions {
+                let piece = match self.get_piece(p) {
+                    Some(piece) if !stable[p.row][p.col] => piece,
+                    _ => continue,
+                };
+
+                let anchored = |direction: (isize, isize)| match step::<N>(p, direction) {
+                    None => true,
+                    Some(n) => stable[n.row][n.col] && self.get_piece(n) == Some(piece),
+                };
+
+                let safe = AXES.iter().enumerate().all(|(axis, &(dc, dr))| {
+                    filled[p.row][p.col][axis] || anchored((dc, dr)) || anchored((-dc, -dr))
+                });
+
+                if safe {
+                    stable[p.row][p.col] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        let count = |color| {
+            positions
+                .iter()
+                .filter(|p| stable[p.row][p.col] && self.get_piece(**p) == Some(color))
+                .count()
+        };
+
+        StableDiscs {
+            black: count(ReversiPiece::Black),
+            white: count(ReversiPiece::White),
+            stable,
+        }
+    }
+
+    /// The result of the game, if it's already certain: either the game is over,
+    /// or one player has more than half the board in stable discs,
+    /// so their final disc count is bound to be the larger.
+    pub fn decided_result(&self) -> Option<GameResult> {
+        if self.is_game_over() {
+            return self.game_result();
+        }
+
+        let stable = self.stable_discs();
+        let half = N * N / 2;
+
+        let majority = [PlayerColor::Black, PlayerColor::White]
+            .iter()
+            .copied()
+            .find(|&player| stable.count(player) > half)?;
+
+        let winner = match self.variant().scoring {
+            Scoring::MostDiscs => majority,
+            Scoring::FewestDiscs => majority.opponent(),
+        };
+
+        Some(match winner {
+            PlayerColor::Black => GameResult::BlackWins,
+            PlayerColor::White => GameResult::WhiteWins,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ReversiState, ReversiVariant};
+    use lib_boardgame::PositionSetup;
+
+    fn parse(rows: &[&str], side: &str) -> ReversiState {
+        ReversiState::parse_position(&format!("{} {}", rows.concat(), side)).unwrap()
+    }
+
+    fn notation(stable: &StableDiscs<8>) -> Vec<String> {
+        let mut squares = stable.iter().map(|p| p.to_notation(8)).collect::<Vec<_>>();
+        squares.sort();
+
+        squares
+    }
+
+    #[test]
+    fn initial_position_expects_no_stable_discs() {
+        let stable = ReversiState::initial_state().stable_discs();
+
+        assert_eq!(0, stable.iter().count());
+        assert_eq!(0, stable.count(PlayerColor::Black));
+    }
+
+    #[test]
+    fn stable_discs_expects_anchored_edges_and_corner_propagation() {
+        let state = parse(
+            &[
+                "***O----", "**------", "*-------", "---O*---", "---*O---", "--------", "--------",
+                "--------",
+            ],
+            "O",
+        );
+
+        let stable = state.stable_discs();
+
+        // The a1 corner anchors its edges, and b2 is held in place by its stable neighbors.
+        // White's d1 isn't anchored, so Black could still flip it by playing e1.
+        assert_eq!(vec!["a1", "a2", "a3", "b1", "b2", "c1"], notation(&stable));
+        assert_eq!(6, stable.count(PlayerColor::Black));
+        assert_eq!(0, stable.count(PlayerColor::White));
+    }
+
+    #[test]
+    fn human_friendly_with_stable_discs_expects_stable_glyphs() {
+        let state = parse(
+            &[
+                "**O-----", "--------", "--------", "---O*---", "---*O---", "--------", "--------",
+                "--------",
+            ],
+            "O",
+        );
+
+        assert!(state.human_friendly().contains("1| X X O - "));
+
+        let marked = state.human_friendly_with_stable_discs();
+
+        assert!(marked.contains("1| # # O - "));
+        assert_eq!(
+            state.position_string(),
+            ReversiState::from_board_str(&marked).position_string()[..64].to_owned() + " O"
+        );
+    }
+
+    #[test]
+    fn stable_discs_expects_filled_lines() {
+        // Row 4 is full, so its discs are safe horizontally,
+        // but they can all still be flipped along another line.
+        let state = parse(
+            &[
+                "--------", "--------", "--------", "OOOO****", "---*O---", "--------", "--------",
+                "--------",
+            ],
+            "*",
+        );
+
+        let stable = state.stable_discs();
+
+        assert!(!stable.contains(BoardPosition::from_notation("d4", 8).unwrap()));
+        assert!(!stable.contains(BoardPosition::from_notation("a4", 8).unwrap()));
+
+        // A full board is entirely stable.
+        let full = parse(
+            &[
+                "********", "********", "********", "********", "OOOOOOOO", "OOOOOOOO", "OOOOOOOO",
+                "OOOOOOOO",
+            ],
+            "*",
+        );
+
+        assert_eq!(32, full.stable_discs().count(PlayerColor::Black));
+        assert_eq!(32, full.stable_discs().count(PlayerColor::White));
+    }
+
+    #[test]
+    fn decided_result_expects_win_once_majority_is_stable() {
+        // Black's top five rows are full, with the bottom rows left open.
+        let rows = [
+            "********", "********", "********", "********", "********", "-O------", "--------",
+            "--------",
+        ];
+        let state = parse(&rows, "O");
+
+        assert!(!state.is_game_over());
+        assert_eq!(40, state.stable_discs().count(PlayerColor::Black));
+        assert_eq!(Some(GameResult::BlackWins), state.decided_result());
+
+        let mut anti = state.clone();
+        anti.set_rules(ReversiVariant::anti());
+        assert_eq!(Some(GameResult::WhiteWins), anti.decided_result());
+
+        assert_eq!(None, ReversiState::initial_state().decided_result());
+    }
+}
@@ -1,4 +1,3 @@
-use crate::reversi_gamestate::ReversiState;
 use crate::{BoardPosition, Directions};
 use lib_boardgame::PlayerColor;
 
@@ -18,14 +17,14 @@ pub(crate) struct BoardDirectionIter {
 }
 
 impl BoardDirectionIter {
-    pub fn new(origin: BoardPosition, direction: Directions) -> Self {
+    pub fn new(origin: BoardPosition, direction: Directions, board_size: usize) -> Self {
         if direction.col_dir == 0 && direction.row_dir == 0 {
             panic!("Can't create an iterator with both column and row direction as 0 (this would result in an iterator that never moves)");
         }
 
         BoardDirectionIter {
             direction,
-            board_size: ReversiState::BOARD_SIZE,
+            board_size,
 
             cursor: origin,
         }
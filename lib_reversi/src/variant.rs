@@ -0,0 +1,90 @@
+/// Decides the winner once the game is over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Scoring {
+    /// Standard Reversi: the player with the most discs wins.
+    #[default]
+    MostDiscs,
+
+    /// Anti-Reversi: the player with the fewest discs wins.
+    FewestDiscs,
+}
+
+/// How the discs are set up before the first move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum StartingPosition {
+    /// The standard start, with each player's two discs on a diagonal.
+    #[default]
+    Cross,
+
+    /// Each player's two discs side by side in a column, White's on the left.
+    Parallel,
+
+    /// The cross start, followed by the given number of random legal moves,
+    /// chosen deterministically from the seed (as in "Othello 8-random" openings).
+    RandomOpening { moves: usize, seed: u64 },
+}
+
+/// What happens when the player to move has no legal move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum PassRule {
+    /// The player's only legal move is to pass the turn, which they must play.
+    #[default]
+    PassAsMove,
+
+    /// The turn passes to the opponent automatically, so passing is never a legal move.
+    AutoPass,
+}
+
+/// A set of Reversi rules, selected when constructing a state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct ReversiVariant {
+    pub scoring: Scoring,
+    pub start: StartingPosition,
+    pub passing: PassRule,
+}
+
+impl ReversiVariant {
+    /// Anti-Reversi: standard rules, except the player with the fewest discs wins.
+    pub fn anti() -> Self {
+        Self {
+            scoring: Scoring::FewestDiscs,
+            ..Default::default()
+        }
+    }
+
+    /// Encodes the rules that affect play (but not the starting position) as bytes.
+    pub(crate) fn encode_rules(self) -> [u8; 2] {
+        let scoring = match self.scoring {
+            Scoring::MostDiscs => 0,
+            Scoring::FewestDiscs => 1,
+        };
+
+        let passing = match self.passing {
+            PassRule::PassAsMove => 0,
+            PassRule::AutoPass => 1,
+        };
+
+        [scoring, passing]
+    }
+
+    /// The inverse of encode_rules(), with the default starting position.
+    pub(crate) fn decode_rules(bytes: &[u8]) -> Option<Self> {
+        let scoring = match bytes.first()? {
+            0 => Scoring::MostDiscs,
+            1 => Scoring::FewestDiscs,
+            _ => return None,
+        };
+
+        let passing = match bytes.get(1)? {
+            0 => PassRule::PassAsMove,
+            1 => PassRule::AutoPass,
+            _ => return None,
+        };
+
+        Some(Self {
+            scoring,
+            passing,
+            ..Default::default()
+        })
+    }
+}
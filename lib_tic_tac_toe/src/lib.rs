@@ -23,7 +23,11 @@ impl TicTacToePiece {
 #[cfg(test)]
 mod tests {
     use crate::tic_tac_toe_gamestate::{BoardPosition, TicTacToeAction, TicTacToeState};
-    use lib_boardgame::{GameState, PlayerColor};
+    use lib_boardgame::{
+        GameAgent, GameResult, GameRunner, GameState, GeneralGameRunner, IllegalMoveReason, Oracle,
+        PlayerColor, PositionError, PositionSetup, StateEncoding, TurnChoice, UndoMove,
+    };
+    use std::cell::RefCell;
     use std::str::FromStr;
 
     #[test]
@@ -91,6 +95,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_state_expects_round_trips_encoding() {
+        let mut state = TicTacToeState::initial_state();
+        state.apply_move(TicTacToeAction::from_str("0,2").unwrap());
+        state.apply_move(TicTacToeAction::from_str("1,1").unwrap());
+
+        let decoded = TicTacToeState::decode_state(&state.encode_state()).unwrap();
+
+        assert_eq!(state.human_friendly(), decoded.human_friendly());
+        assert_eq!(state.current_player_turn(), decoded.current_player_turn());
+        assert_eq!(state.legal_moves(), decoded.legal_moves());
+    }
+
+    #[test]
+    fn parse_position_expects_round_trips_position_string() {
+        let mut state = TicTacToeState::initial_state();
+        state.apply_move(TicTacToeAction::from_str("0,2").unwrap());
+        state.apply_move(TicTacToeAction::from_str("1,1").unwrap());
+        state.apply_move(TicTacToeAction::from_str("2,0").unwrap());
+
+        let text = state.position_string();
+        let parsed = TicTacToeState::parse_position(&text).unwrap();
+
+        assert_eq!("X---O---X O", text);
+        assert_eq!(state.encode_state(), parsed.encode_state());
+        assert_eq!(state.legal_moves(), parsed.legal_moves());
+    }
+
+    #[test]
+    fn parse_position_expects_errors_for_impossible_positions() {
+        let impossible = [
+            // Too many O pieces.
+            "OO-------X",
+            // Counts say O is to move.
+            "X-------- X",
+            // Both players have a line.
+            "XXXOOO--- X",
+            // X won, yet O kept playing.
+            "XXXOO-O-- X",
+        ];
+
+        for text in impossible.iter() {
+            assert!(
+                matches!(
+                    TicTacToeState::parse_position(text),
+                    Err(PositionError::Impossible(_))
+                ),
+                "expected an impossible position: {}",
+                text
+            );
+        }
+
+        assert!(matches!(
+            TicTacToeState::parse_position("X-------"),
+            Err(PositionError::Malformed(_))
+        ));
+    }
+
     #[test]
     #[should_panic]
     fn applying_move_nonempty_location_expects_panic() {
@@ -104,4 +166,182 @@ mod tests {
         // But the same location should panic.
         state.apply_move(TicTacToeAction::from_str("1,1").unwrap());
     }
+
+    #[test]
+    fn try_apply_move_when_illegal_expects_error_and_unchanged_state() {
+        let mut state = TicTacToeState::initial_state();
+        state.apply_move(TicTacToeAction::from_str("1,1").unwrap());
+        let before = state.human_friendly();
+
+        let occupied = state.try_apply_move(TicTacToeAction::from_str("1,1").unwrap());
+        assert_eq!(IllegalMoveReason::Occupied, occupied.unwrap_err().reason);
+
+        let out_of_bounds = state.try_apply_move(TicTacToeAction(BoardPosition::new(3, 0)));
+        assert_eq!(
+            IllegalMoveReason::OutOfBounds,
+            out_of_bounds.unwrap_err().reason
+        );
+
+        assert_eq!(before, state.human_friendly());
+        assert_eq!(PlayerColor::White, state.current_player_turn());
+
+        assert!(state
+            .try_apply_move(TicTacToeAction::from_str("0,0").unwrap())
+            .is_ok());
+        assert_eq!(PlayerColor::Black, state.current_player_turn());
+    }
+
+    /// An agent that always picks the center, whether or not it is taken.
+    struct CenterAgent(PlayerColor);
+
+    impl GameAgent<TicTacToeState> for CenterAgent {
+        fn pick_move(
+            &self,
+            _state: &TicTacToeState,
+            _legal: &[TicTacToeAction],
+        ) -> TicTacToeAction {
+            TicTacToeAction(BoardPosition::new(1, 1))
+        }
+
+        fn player_color(&self) -> PlayerColor {
+            self.0
+        }
+    }
+
+    #[test]
+    fn play_to_end_when_agent_repeats_illegal_move_expects_forfeit() {
+        let black = CenterAgent(PlayerColor::Black);
+        let white = CenterAgent(PlayerColor::White);
+
+        // Black takes the center, then White can only pick the occupied center.
+        let result = GeneralGameRunner::play_to_end(&black, &white);
+
+        assert_eq!(GameResult::BlackWins, result);
+    }
+
+    #[test]
+    fn undo_move_expects_restores_prior_state() {
+        let mut state = TicTacToeState::initial_state();
+        state.apply_move(TicTacToeAction::from_str("0,0").unwrap());
+        let before = state.clone();
+
+        let undo = state.make_move(TicTacToeAction::from_str("1,1").unwrap());
+        state.undo_move(undo);
+
+        assert_eq!(before.human_friendly(), state.human_friendly());
+        assert_eq!(before.current_player_turn(), state.current_player_turn());
+        assert_eq!(before.legal_moves(), state.legal_moves());
+    }
+
+    /// An agent that plays a fixed sequence of turns.
+    struct ScriptedAgent {
+        color: PlayerColor,
+        turns: RefCell<Vec<TurnChoice<TicTacToeAction>>>,
+    }
+
+    impl ScriptedAgent {
+        fn new(color: PlayerColor, turns: &[&str]) -> Self {
+            let turns = turns
+                .iter()
+                .rev()
+                .map(|&t| match t {
+                    "undo" => TurnChoice::TakeBack,
+                    t => TurnChoice::Play(TicTacToeAction::from_str(t).unwrap()),
+                })
+                .collect();
+
+            Self {
+                color,
+                turns: RefCell::new(turns),
+            }
+        }
+    }
+
+    impl GameAgent<TicTacToeState> for ScriptedAgent {
+        fn pick_move(
+            &self,
+            _state: &TicTacToeState,
+            _legal: &[TicTacToeAction],
+        ) -> TicTacToeAction {
+            unreachable!("Only pick_turn() is used when taking back is allowed.")
+        }
+
+        fn pick_turn(
+            &self,
+            _state: &TicTacToeState,
+            _legal: &[TicTacToeAction],
+        ) -> TurnChoice<TicTacToeAction> {
+            self.turns.borrow_mut().pop().expect("Ran out of turns.")
+        }
+
+        fn player_color(&self) -> PlayerColor {
+            self.color
+        }
+    }
+
+    #[test]
+    fn play_to_end_with_take_backs_expects_undoes_both_moves() {
+        // White blocks Black's column at 0,2, so Black takes back 0,1 (undoing the block too),
+        // then claims 0,2 itself. If the block weren't undone, Black's 0,2 would be illegal.
+        let black = ScriptedAgent::new(PlayerColor::Black, &["0,0", "0,1", "undo", "0,2", "0,1"]);
+        let white = ScriptedAgent::new(PlayerColor::White, &["1,1", "0,2", "1,0"]);
+
+        let result = GeneralGameRunner::play_to_end_with_take_backs(&black, &white);
+
+        assert_eq!(GameResult::BlackWins, result);
+    }
+
+    #[test]
+    fn perft_expects_known_node_counts() {
+        let state = TicTacToeState::initial_state();
+
+        // Games that end early with a win are leaves at every greater depth,
+        // so from depth 8 on, the count is the number of possible games.
+        let expected = [9, 72, 504, 3024, 15120, 56160, 154944, 255168, 255168];
+
+        for (depth, &count) in expected.iter().enumerate() {
+            assert_eq!(count, lib_boardgame::perft(&state, depth + 1));
+        }
+
+        assert_eq!(255168, lib_boardgame::perft(&state, 10));
+    }
+
+    #[test]
+    fn random_playouts_expect_conformance() {
+        lib_boardgame::conformance::assert_conformance::<TicTacToeState>();
+    }
+
+    #[test]
+    fn solve_expects_draw_from_every_legal_position_count() {
+        let initial = TicTacToeState::initial_state();
+        let oracle = Oracle::solve(&initial);
+
+        // Every legal position, including finished games.
+        assert_eq!(5478, oracle.len());
+        assert_eq!(Some(GameResult::Tie), oracle.result(&initial));
+
+        // No first move loses.
+        assert_eq!(9, oracle.optimal_moves(&initial).unwrap().len());
+    }
+
+    #[test]
+    fn optimal_moves_when_opponent_threatens_expects_only_block() {
+        let mut state = TicTacToeState::initial_state();
+
+        // Create this state, with White to move:
+        // ___
+        // _XX
+        // O__
+        state.apply_move(TicTacToeAction(BoardPosition::new(1, 1)));
+        state.apply_move(TicTacToeAction(BoardPosition::new(0, 0)));
+        state.apply_move(TicTacToeAction(BoardPosition::new(2, 1)));
+
+        let oracle = Oracle::solve(&TicTacToeState::initial_state());
+
+        assert_eq!(Some(GameResult::Tie), oracle.result(&state));
+        assert_eq!(
+            vec![TicTacToeAction(BoardPosition::new(0, 1))],
+            oracle.optimal_moves(&state).unwrap()
+        );
+    }
 }
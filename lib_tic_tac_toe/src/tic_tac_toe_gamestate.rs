@@ -1,8 +1,12 @@
 use crate::{TicTacToePiece, BOARD_SIZE};
-use lib_boardgame::{GameAction, GameState, PlayerColor};
+use lib_boardgame::{
+    split_position_text, GameAction, GameState, IllegalMoveError, IllegalMoveReason, Mobility,
+    MoveOrdering, PlayerColor, PositionError, PositionSetup, StateEncoding, UndoMove,
+};
 use std::fmt;
 
-type Board = [[Option<TicTacToePiece>; BOARD_SIZE]; BOARD_SIZE];
+/// The pieces on the board, indexed by [row][col], with the top row first.
+pub type Board = [[Option<TicTacToePiece>; BOARD_SIZE]; BOARD_SIZE];
 
 #[derive(Clone, Debug)]
 pub struct TicTacToeState {
@@ -28,6 +32,10 @@ impl GameAction for TicTacToeAction {
     fn is_forced_pass(self) -> bool {
         false
     }
+
+    fn input_hint() -> String {
+        "col,row".to_owned()
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -77,6 +85,12 @@ impl std::str::FromStr for TicTacToeAction {
     }
 }
 
+impl Default for TicTacToeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TicTacToeState {
     pub fn new() -> Self {
         let board: Board = [[None; BOARD_SIZE]; BOARD_SIZE];
@@ -192,11 +206,11 @@ impl TicTacToeState {
         // Top-left to bottom-right
         {
             let top_left_first_piece = self.get_piece(BoardPosition::new(0, BOARD_SIZE - 1));
-            if top_left_first_piece.is_some() {
+            if let Some(top_left_first_piece) = top_left_first_piece {
                 for xy in 1..BOARD_SIZE {
                     let piece = self.get_piece(BoardPosition::new(xy, BOARD_SIZE - xy - 1));
 
-                    if piece != top_left_first_piece {
+                    if piece != Some(top_left_first_piece) {
                         break;
                     }
 
@@ -204,7 +218,7 @@ impl TicTacToeState {
                         // We made it to the final position without failing,
                         // so we must have found a full diagonal populated by one player's piece.
                         // Therefore, the game is won.
-                        return Some(top_left_first_piece.unwrap().player_color());
+                        return Some(top_left_first_piece.player_color());
                     }
                 }
             }
@@ -213,11 +227,11 @@ impl TicTacToeState {
         // Bottom-left to top-right
         {
             let bottom_left_first_piece = self.get_piece(BoardPosition::new(0, 0));
-            if bottom_left_first_piece.is_some() {
+            if let Some(bottom_left_first_piece) = bottom_left_first_piece {
                 for xy in 1..BOARD_SIZE {
                     let piece = self.get_piece(BoardPosition::new(xy, xy));
 
-                    if piece != bottom_left_first_piece {
+                    if piece != Some(bottom_left_first_piece) {
                         break;
                     }
 
@@ -225,7 +239,7 @@ impl TicTacToeState {
                         // We made it to the final position without failing,
                         // so we must have found a full diagonal populated by one player's piece.
                         // Therefore, the game is won.
-                        return Some(bottom_left_first_piece.unwrap().player_color());
+                        return Some(bottom_left_first_piece.player_color());
                     }
                 }
             }
@@ -234,6 +248,17 @@ impl TicTacToeState {
         None
     }
 
+    /// True if the given piece fills any row, column, or diagonal.
+    fn has_line(&self, piece: TicTacToePiece) -> bool {
+        let owns =
+            |col: usize, row: usize| self.get_piece(BoardPosition::new(col, row)) == Some(piece);
+        let all = |f: &dyn Fn(usize) -> bool| (0..BOARD_SIZE).all(f);
+
+        (0..BOARD_SIZE).any(|i| all(&|j| owns(j, i)) || all(&|j| owns(i, j)))
+            || all(&|i| owns(i, i))
+            || all(&|i| owns(i, BOARD_SIZE - i - 1))
+    }
+
     /// Returns the possible moves the given player can make for the current state.
     /// In TicTacToe, any empty spot is a legal position for either player.
     fn calc_legal_moves(&self, _player: PlayerColor) -> Vec<TicTacToeAction> {
@@ -259,8 +284,205 @@ impl TicTacToeState {
     }
 
     fn update_stored_state_values(&mut self) {
-        self.cur_state_legal_moves = self.calc_legal_moves(self.current_player_turn);
         self.is_game_over = self.calc_is_game_over();
+
+        // Once a player has won, the empty spots are no longer legal moves.
+        self.cur_state_legal_moves = if self.is_game_over {
+            Vec::new()
+        } else {
+            self.calc_legal_moves(self.current_player_turn)
+        };
+    }
+}
+
+impl UndoMove for TicTacToeState {
+    /// The position of the placed piece is all that's needed to undo a move.
+    type Undo = TicTacToeAction;
+
+    fn make_move(&mut self, action: TicTacToeAction) -> TicTacToeAction {
+        self.apply_move(action);
+
+        action
+    }
+
+    fn undo_move(&mut self, undo: TicTacToeAction) {
+        self.set_piece(undo.0, None);
+
+        self.current_player_turn = self.current_player_turn.opponent();
+        self.update_stored_state_values();
+    }
+}
+
+impl Mobility for TicTacToeState {
+    fn legal_moves_for(&self, player: PlayerColor) -> Vec<TicTacToeAction> {
+        self.calc_legal_moves(player)
+    }
+}
+
+impl StateEncoding for TicTacToeState {
+    /// Encodes the player to move, followed by one byte per board position
+    /// (0 for empty, 1 for X, 2 for O).
+    fn encode_state(&self) -> Vec<u8> {
+        let mut bytes = vec![self.current_player_turn.to_byte()];
+
+        bytes.extend(self.board.iter().flatten().map(|piece| match piece {
+            None => 0,
+            Some(TicTacToePiece::X) => 1,
+            Some(TicTacToePiece::O) => 2,
+        }));
+
+        bytes
+    }
+
+    fn decode_state(bytes: &[u8]) -> Option<Self> {
+        let (&turn, cells) = bytes.split_first()?;
+
+        if cells.len() != BOARD_SIZE * BOARD_SIZE {
+            return None;
+        }
+
+        let mut state = Self::new();
+        state.current_player_turn = PlayerColor::from_byte(turn)?;
+
+        for (i, &cell) in cells.iter().enumerate() {
+            let piece = match cell {
+                0 => None,
+                1 => Some(TicTacToePiece::X),
+                2 => Some(TicTacToePiece::O),
+                _ => return None,
+            };
+
+            // The board is stored top row first (see transform_coords).
+            let position = BoardPosition::new(i % BOARD_SIZE, BOARD_SIZE - 1 - i / BOARD_SIZE);
+            state.set_piece(position, piece);
+        }
+
+        state.update_stored_state_values();
+
+        Some(state)
+    }
+}
+
+impl PositionSetup for TicTacToeState {
+    type Board = Board;
+
+    /// Creates a state from a board indexed by [row][col], with the top row first.
+    /// X always moves first, so the piece counts must agree with the player to move,
+    /// and at most one player can have completed a line.
+    fn from_position(board: Board, side_to_move: PlayerColor) -> Result<Self, PositionError> {
+        let mut state = Self::new();
+        state.board = board;
+        state.x_piece_count = board
+            .iter()
+            .flatten()
+            .filter(|p| **p == Some(TicTacToePiece::X))
+            .count();
+        state.o_piece_count = board
+            .iter()
+            .flatten()
+            .filter(|p| **p == Some(TicTacToePiece::O))
+            .count();
+        state.current_player_turn = side_to_move;
+
+        let expected_turn = match state.x_piece_count.checked_sub(state.o_piece_count) {
+            Some(0) => PlayerColor::Black,
+            Some(1) => PlayerColor::White,
+            _ => {
+                return Err(PositionError::Impossible(format!(
+                    "{} X pieces and {} O pieces",
+                    state.x_piece_count, state.o_piece_count
+                )))
+            }
+        };
+
+        if side_to_move != expected_turn {
+            return Err(PositionError::Impossible(format!(
+                "{} X pieces and {} O pieces, but {:?} is to move",
+                state.x_piece_count, state.o_piece_count, side_to_move
+            )));
+        }
+
+        let x_won = state.has_line(TicTacToePiece::X);
+        let o_won = state.has_line(TicTacToePiece::O);
+
+        if x_won && o_won {
+            return Err(PositionError::Impossible(
+                "both players have won".to_owned(),
+            ));
+        }
+
+        // The winner must have made the last move.
+        if (x_won && side_to_move != PlayerColor::White)
+            || (o_won && side_to_move != PlayerColor::Black)
+        {
+            return Err(PositionError::Impossible(
+                "play continued after the game was won".to_owned(),
+            ));
+        }
+
+        state.update_stored_state_values();
+
+        Ok(state)
+    }
+
+    /// Parses 9 squares, row by row from the top, with '-' for empty,
+    /// followed by the player to move ('X' or 'O').
+    fn parse_position(text: &str) -> Result<Self, PositionError> {
+        let (squares, side_to_move) = split_position_text(text, BOARD_SIZE * BOARD_SIZE)?;
+
+        let mut board: Board = [[None; BOARD_SIZE]; BOARD_SIZE];
+
+        for (i, c) in squares.into_iter().enumerate() {
+            board[i / BOARD_SIZE][i % BOARD_SIZE] = match c {
+                '-' | '_' | '.' => None,
+                'X' | 'x' => Some(TicTacToePiece::X),
+                'O' | 'o' => Some(TicTacToePiece::O),
+                _ => {
+                    return Err(PositionError::Malformed(format!(
+                        "unrecognized square: {:?}",
+                        c
+                    )))
+                }
+            };
+        }
+
+        Self::from_position(board, side_to_move)
+    }
+
+    fn position_string(&self) -> String {
+        let mut result = self
+            .board
+            .iter()
+            .flatten()
+            .map(|piece| match piece {
+                None => '-',
+                Some(TicTacToePiece::X) => 'X',
+                Some(TicTacToePiece::O) => 'O',
+            })
+            .collect::<String>();
+
+        result.push(' ');
+        result.push(match self.current_player_turn {
+            PlayerColor::Black => 'X',
+            PlayerColor::White => 'O',
+        });
+
+        result
+    }
+}
+
+impl MoveOrdering for TicTacToeState {
+    /// Prioritizes the center, then the corners, then the edges,
+    /// by how many lines pass through each position.
+    fn move_priority(&self, action: TicTacToeAction) -> f32 {
+        let BoardPosition { col, row } = action.0;
+        let is_middle = |i| i == BOARD_SIZE / 2;
+
+        match (is_middle(col), is_middle(row)) {
+            (true, true) => 2.0,
+            (false, false) => 1.0,
+            _ => 0.0,
+        }
     }
 }
 
@@ -312,9 +534,9 @@ impl GameState for TicTacToeState {
         uninitialized
     }
 
-    /// Returns the possible moves the given player can make for the current state.
+    /// Returns the possible moves the player to move can make in the current state.
     /// In TicTacToe, any empty spot is a legal position for either player.
-    fn legal_moves(&self, _player: PlayerColor) -> &[Self::Action] {
+    fn legal_moves(&self) -> &[Self::Action] {
         self.cur_state_legal_moves.as_slice()
     }
 
@@ -342,6 +564,26 @@ impl GameState for TicTacToeState {
         self.update_stored_state_values();
     }
 
+    fn try_apply_move(&mut self, action: TicTacToeAction) -> Result<(), IllegalMoveError> {
+        let illegal = |reason| Err(IllegalMoveError::new(action, reason));
+
+        if self.is_game_over() {
+            return illegal(IllegalMoveReason::GameOver);
+        }
+
+        if !Self::within_board_bounds(action.0) {
+            return illegal(IllegalMoveReason::OutOfBounds);
+        }
+
+        if self.get_piece(action.0).is_some() {
+            return illegal(IllegalMoveReason::Occupied);
+        }
+
+        self.apply_move(action);
+
+        Ok(())
+    }
+
     /// Returns the current player whose turn it currently is.
     fn current_player_turn(&self) -> PlayerColor {
         self.current_player_turn
@@ -48,11 +48,11 @@ impl<T: Send + Sync> Node for ArcNode<T> {
         self.parent.upgrade().clone()
     }
 
-    fn children_read(&self) -> AtomicRef<Vec<Self::Handle>> {
+    fn children_read(&self) -> AtomicRef<'_, Vec<Self::Handle>> {
         self.children.read()
     }
 
-    fn children_write_lock(&self) -> WriteOnceWriteGuard<Vec<Self::Handle>> {
+    fn children_write_lock(&self) -> WriteOnceWriteGuard<'_, Vec<Self::Handle>> {
         self.children.write_lock()
     }
 
@@ -0,0 +1,278 @@
+use crate::monte_carlo_data::MctsData;
+use crate::tree::Node;
+use lib_boardgame::{GameResult, GameState};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt::Write;
+
+/// Options limiting which part of a tree is exported.
+#[derive(Copy, Clone, Debug)]
+pub struct ExportOptions {
+    /// The maximum depth below the exported node to include.
+    /// A depth of 0 exports only the node itself.
+    pub max_depth: usize,
+
+    /// Children with fewer plays than this are left out.
+    pub min_plays: usize,
+
+    /// When true, each node's game state is included, in its human-friendly form.
+    pub include_states: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            min_plays: 1,
+            include_states: false,
+        }
+    }
+}
+
+/// A snapshot of a node's MCTS statistics, along with the snapshots of its exported children.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedNode {
+    /// The action taken to reach this node, or None for the exported root.
+    pub action: Option<String>,
+    pub wins: usize,
+    pub plays: usize,
+    pub mean_reward: f32,
+    pub mean_margin: f32,
+    pub tree_size: usize,
+    pub is_saturated: bool,
+    pub worst_wins: usize,
+    pub worst_plays: usize,
+    pub terminal_count: usize,
+    pub terminal_wins_count: usize,
+    pub proven: Option<String>,
+
+    /// The game state at this node, if states were included in the export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    pub children: Vec<ExportedNode>,
+}
+
+impl ExportedNode {
+    /// Captures the given node and its descendants, as limited by the options.
+    pub fn capture<TNode, TState>(node: &TNode, options: ExportOptions) -> Self
+    where
+        TNode: Node<Data = MctsData<TState>>,
+        TState: GameState,
+    {
+        Self::capture_depth(node, options, 0)
+    }
+
+    fn capture_depth<TNode, TState>(node: &TNode, options: ExportOptions, depth: usize) -> Self
+    where
+        TNode: Node<Data = MctsData<TState>>,
+        TState: GameState,
+    {
+        let data = node.data();
+        let (wins, plays) = data.wins_plays();
+        let (worst_wins, worst_plays) = data.worst_case_wins_plays();
+
+        let children = if depth < options.max_depth {
+            node.children_read()
+                .iter()
+                .map(|c| c.borrow())
+                .filter(|c| c.data().wins_plays().1 >= options.min_plays)
+                .map(|c| Self::capture_depth(c, options, depth + 1))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            action: data.action().map(|a| a.to_string()),
+            wins,
+            plays,
+            mean_reward: data.mean_reward(),
+            mean_margin: data.mean_margin(),
+            tree_size: data.tree_size(),
+            is_saturated: data.is_saturated(),
+            worst_wins,
+            worst_plays,
+            terminal_count: data.terminal_count(),
+            terminal_wins_count: data.terminal_wins_count(),
+            proven: data.proven_result().map(result_name),
+            state: if options.include_states {
+                Some(data.state().human_friendly())
+            } else {
+                None
+            },
+            children,
+        }
+    }
+
+    /// The exported tree as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("An exported tree is always serializable.")
+    }
+
+    /// The exported tree as a Graphviz DOT digraph.
+    /// Saturated nodes are shaded, and proven nodes are outlined in bold.
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("digraph mcts {\n    node [shape=box, fontname=\"monospace\"];\n");
+        let mut next_id = 0;
+
+        self.write_dot(&mut dot, &mut next_id);
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Writes this node and its children, returning the id given to this node.
+    fn write_dot(&self, dot: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let action = self.action.as_deref().unwrap_or("root");
+        let ratio = self.wins as f32 / usize::max(1, self.plays) as f32;
+
+        let mut label = format!(
+            "{}\\nW/P: {}/{} ({:.3})\\nWW/WP: {}/{}\\nTerm: {}/{}",
+            escape(action),
+            self.wins,
+            self.plays,
+            ratio,
+            self.worst_wins,
+            self.worst_plays,
+            self.terminal_wins_count,
+            self.terminal_count
+        );
+
+        if let Some(proven) = &self.proven {
+            write!(label, "\\nProven: {}", proven).unwrap();
+        }
+
+        let mut style = Vec::new();
+        if self.is_saturated {
+            style.push("filled");
+        }
+        if self.proven.is_some() {
+            style.push("bold");
+        }
+
+        writeln!(
+            dot,
+            "    n{} [label=\"{}\", style=\"{}\"];",
+            id,
+            label,
+            style.join(",")
+        )
+        .unwrap();
+
+        for child in &self.children {
+            let child_id = child.write_dot(dot, next_id);
+            writeln!(dot, "    n{} -> n{};", id, child_id).unwrap();
+        }
+
+        id
+    }
+}
+
+fn result_name(result: GameResult) -> String {
+    format!("{:?}", result)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_tree::ArcNode;
+    use lib_tic_tac_toe::tic_tac_toe_gamestate::{TicTacToeAction, TicTacToeState};
+    use std::str::FromStr;
+
+    fn make_tree() -> ArcNode<MctsData<TicTacToeState>> {
+        let state = TicTacToeState::initial_state();
+        let root = ArcNode::new_root(MctsData::new(state.clone(), 3, 2, None));
+
+        let actions = ["1,1", "0,0", "2,2"]
+            .iter()
+            .map(|s| TicTacToeAction::from_str(s).unwrap());
+
+        let children = actions
+            .zip([(2, 1), (1, 1), (0, 0)].iter())
+            .map(|(a, &(plays, wins))| {
+                root.new_child(MctsData::new(state.next_state(a), plays, wins, Some(a)))
+            })
+            .collect::<Vec<_>>();
+
+        let grandchild = children[0].new_child(MctsData::new(state.clone(), 1, 0, None));
+        children[0].children_write_lock().write(vec![grandchild]);
+
+        root.children_write_lock().write(children);
+
+        root
+    }
+
+    #[test]
+    fn capture_expects_respects_depth_and_plays() {
+        let root = make_tree();
+
+        let options = ExportOptions {
+            max_depth: 1,
+            ..Default::default()
+        };
+        let exported = ExportedNode::capture(&root, options);
+
+        assert_eq!(None, exported.action);
+        assert_eq!(3, exported.plays);
+
+        // The child with no plays is filtered out, and grandchildren are beyond the depth.
+        assert_eq!(2, exported.children.len());
+        assert!(exported.children.iter().all(|c| c.children.is_empty()));
+
+        let deeper = ExportedNode::capture(&root, ExportOptions::default());
+        assert_eq!(1, deeper.children[0].children.len());
+    }
+
+    #[test]
+    fn to_dot_expects_one_edge_per_child() {
+        let root = make_tree();
+        let dot = ExportedNode::capture(&root, ExportOptions::default()).to_dot();
+
+        assert!(dot.starts_with("digraph mcts {"));
+        assert_eq!(3, dot.matches(" -> ").count());
+        assert!(dot.contains("n0 -> n1;"));
+    }
+
+    #[test]
+    fn to_json_expects_round_trips_fields() {
+        let root = make_tree();
+        let json = ExportedNode::capture(&root, ExportOptions::default()).to_json();
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(3, value["plays"]);
+        assert_eq!(2, value["wins"]);
+        assert_eq!(2, value["children"][0]["plays"]);
+        assert!(value["children"][0]["action"].is_string());
+        assert!(value.get("state").is_none());
+
+        let parsed: ExportedNode = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            ExportedNode::capture(&root, ExportOptions::default()),
+            parsed
+        );
+    }
+
+    #[test]
+    fn capture_with_states_expects_human_friendly_states() {
+        let root = make_tree();
+        let options = ExportOptions {
+            include_states: true,
+            ..Default::default()
+        };
+
+        let exported = ExportedNode::capture(&root, options);
+
+        assert_eq!(Some(root.data().state().human_friendly()), exported.state);
+    }
+}
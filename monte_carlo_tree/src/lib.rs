@@ -2,7 +2,10 @@ mod util;
 mod write_once_lock;
 
 pub mod arc_tree;
+pub mod export;
 pub mod monte_carlo_data;
+pub mod persist;
+pub mod principal_variation;
 
 /// This describes the general Node trait that can be used for making trees (specifically, monte-carlo trees)
 pub mod tree;
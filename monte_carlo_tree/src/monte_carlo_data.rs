@@ -1,10 +1,38 @@
-use crate::util::clone_atomic_usize;
-use crate::write_once_lock::{WriteOnceLock, WriteOnceWriteGuard};
+use crate::util::{clone_atomic_isize, clone_atomic_usize};
+use crate::write_once_lock::WriteOnceLock;
 use lib_boardgame::{GameResult, GameState};
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Mutex;
 
+/// Rewards are accumulated atomically as fixed-point integers,
+/// where a reward of 1.0 is stored as this many units.
+const REWARD_SCALE: usize = 1_000_000;
+
+/// Encodings of a proven game result, so it can be stored in an atomic.
+const UNPROVEN: u8 = 0;
+const PROVEN_BLACK_WINS: u8 = 1;
+const PROVEN_WHITE_WINS: u8 = 2;
+const PROVEN_TIE: u8 = 3;
+
+fn encode_proven(result: GameResult) -> u8 {
+    match result {
+        GameResult::BlackWins => PROVEN_BLACK_WINS,
+        GameResult::WhiteWins => PROVEN_WHITE_WINS,
+        GameResult::Tie => PROVEN_TIE,
+    }
+}
+
+fn decode_proven(encoded: u8) -> Option<GameResult> {
+    match encoded {
+        PROVEN_BLACK_WINS => Some(GameResult::BlackWins),
+        PROVEN_WHITE_WINS => Some(GameResult::WhiteWins),
+        PROVEN_TIE => Some(GameResult::Tie),
+        _ => None,
+    }
+}
+
 /// A data struct containing the results of MCTS for a single action.
 #[derive(Default, Clone)]
 pub struct MctsResult<TState: GameState> {
@@ -20,6 +48,14 @@ pub struct MctsResult<TState: GameState> {
     /// The count of plays during MCTS simulation for this action.
     pub plays: usize,
 
+    /// The mean reward of the simulations for this action, in the range [0, 1].
+    /// When MCTS only optimizes for wins, this is the same as wins / plays.
+    pub mean_reward: f32,
+
+    /// The mean final score margin (the player's score minus the opponent's score)
+    /// of the simulations for this action.
+    pub mean_margin: f32,
+
     /// True if MCTS explored every possible outcome resulting from this action.
     pub is_saturated: bool,
 
@@ -41,6 +77,10 @@ pub struct MctsResult<TState: GameState> {
     /// The count of descendant states resulting from this action that
     /// were fully explored to saturation.
     pub descendants_saturated_count: usize,
+
+    /// The result of the game under perfect play after taking this action,
+    /// if MCTS was able to prove it, or None otherwise.
+    pub proven: Option<GameResult>,
 }
 
 impl<TState> fmt::Debug for MctsResult<TState>
@@ -49,21 +89,30 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let sat_display = if self.is_saturated { " (S)" } else { "" };
+        let proven_display = match self.proven {
+            Some(GameResult::BlackWins) => " (Proven: B)",
+            Some(GameResult::WhiteWins) => " (Proven: W)",
+            Some(GameResult::Tie) => " (Proven: T)",
+            None => "",
+        };
 
         write!(
             f,
-            "A: {:?} P: {:>10?} W: {:>10?} ({:.3}) TS: {:>10?} Term: {:?}/{:?} WW/WP: {}/{} Sat: {:?}{}",
+            "A: {:?} P: {:>10?} W: {:>10?} ({:.3}) R: {:.3} M: {:>+7.2} TS: {:>10?} Term: {:?}/{:?} WW/WP: {}/{} Sat: {:?}{}{}",
             self.action,
             self.plays,
             self.wins,
             self.wins as f32 / self.plays as f32,
+            self.mean_reward,
+            self.mean_margin,
             self.tree_size,
             self.terminal_wins_count,
             self.terminal_count,
             self.worst_wins,
             self.worst_plays,
             self.descendants_saturated_count,
-            sat_display
+            sat_display,
+            proven_display
         )
     }
 }
@@ -86,6 +135,21 @@ where
     /// The count of times this node has resulted in a win during MCTS.
     wins: AtomicUsize,
 
+    /// The sum of the rewards backpropagated through this node during MCTS,
+    /// stored as a fixed-point value (see REWARD_SCALE).
+    reward: AtomicUsize,
+
+    /// The sum of the final score margins backpropagated through this node during MCTS.
+    margin: AtomicIsize,
+
+    /// The count of simulations, through this node's parent, in which this node's action
+    /// was played by the same player at any later point (All-Moves-As-First).
+    amaf_plays: AtomicUsize,
+
+    /// The sum of the rewards of the simulations counted in amaf_plays,
+    /// stored as a fixed-point value (see REWARD_SCALE).
+    amaf_reward: AtomicUsize,
+
     /// True if this nodeh as been expanded already during MCTS.
     is_expanded: AtomicBool,
 
@@ -118,6 +182,10 @@ where
     /// of the worst-case scenario when following this path
     sat_worst_case_ratio: (AtomicUsize, AtomicUsize),
 
+    /// The game result this node's state leads to under perfect play,
+    /// once it has been proven (see MCTS-Solver), encoded as a u8.
+    proven_result: AtomicU8,
+
     /// A mutex lock that can be used to guarantee exclusion during critical behavior on this node.
     sim_lock: Mutex<()>,
 }
@@ -144,6 +212,10 @@ where
 
         let plays = clone_atomic_usize(&self.plays);
         let wins = clone_atomic_usize(&self.wins);
+        let reward = clone_atomic_usize(&self.reward);
+        let margin = clone_atomic_isize(&self.margin);
+        let amaf_plays = clone_atomic_usize(&self.amaf_plays);
+        let amaf_reward = clone_atomic_usize(&self.amaf_reward);
         let children_count = clone_atomic_usize(&self.children_count);
         let children_saturated_count = clone_atomic_usize(&self.children_saturated_count);
         let descendants_saturated_count = clone_atomic_usize(&self.descendants_saturated_count);
@@ -161,12 +233,17 @@ where
             end_state_result,
             plays,
             wins,
+            reward,
+            margin,
+            amaf_plays,
+            amaf_reward,
             children_count,
             children_saturated_count,
             is_expanded: AtomicBool::new(self.is_expanded()),
             tree_size,
             terminal_count,
             sat_worst_case_ratio,
+            proven_result: AtomicU8::new(self.proven_result.load(Ordering::SeqCst)),
             descendants_saturated_count,
             terminal_wins_count,
             sim_lock: Mutex::new(()),
@@ -185,6 +262,8 @@ where
         Self {
             wins,
             plays,
+            mean_reward: data.mean_reward(),
+            mean_margin: data.mean_margin(),
             action: data
                 .action()
                 .expect("can't convert to MctsResult without an action"),
@@ -196,6 +275,7 @@ where
             descendants_saturated_count: data.descendants_saturated_count(),
             worst_wins: wwins,
             worst_plays: wplays,
+            proven: data.proven_result(),
         }
     }
 }
@@ -211,6 +291,10 @@ where
 
             plays: AtomicUsize::new(plays),
             wins: AtomicUsize::new(wins),
+            reward: AtomicUsize::new(wins * REWARD_SCALE),
+            margin: AtomicIsize::new(0),
+            amaf_plays: AtomicUsize::new(0),
+            amaf_reward: AtomicUsize::new(0),
             is_expanded: AtomicBool::new(false),
 
             sim_lock: Mutex::new(()),
@@ -224,6 +308,7 @@ where
             terminal_count: Default::default(),
             terminal_wins_count: Default::default(),
             sat_worst_case_ratio: (Default::default(), Default::default()),
+            proven_result: AtomicU8::new(UNPROVEN),
         }
     }
 
@@ -252,6 +337,49 @@ where
         (wins, plays)
     }
 
+    /// The reward accumulated by this node, and its play count.
+    /// Like wins_plays(), the reward is always loaded before the plays.
+    pub fn reward_plays(&self) -> (f64, usize) {
+        // Load the reward first, for the same reason wins_plays() loads wins first:
+        // plays are always counted before the reward is added.
+        let reward = self.reward.load(Ordering::SeqCst);
+        let plays = self.plays.load(Ordering::SeqCst);
+
+        (reward as f64 / REWARD_SCALE as f64, plays)
+    }
+
+    /// The mean reward of this node over all its plays, or 0 if it has never been played.
+    pub fn mean_reward(&self) -> f32 {
+        let (reward, plays) = self.reward_plays();
+
+        if plays == 0 {
+            0.0
+        } else {
+            (reward / plays as f64) as f32
+        }
+    }
+
+    /// The mean final score margin of this node over all its plays, or 0 if it has never been played.
+    pub fn mean_margin(&self) -> f32 {
+        let margin = self.margin.load(Ordering::SeqCst);
+        let plays = self.plays.load(Ordering::SeqCst);
+
+        if plays == 0 {
+            0.0
+        } else {
+            margin as f32 / plays as f32
+        }
+    }
+
+    /// The All-Moves-As-First reward accumulated by this node, and its AMAF play count.
+    pub fn amaf_reward_plays(&self) -> (f64, usize) {
+        // Load the reward first, as reward_plays() does.
+        let reward = self.amaf_reward.load(Ordering::SeqCst);
+        let plays = self.amaf_plays.load(Ordering::SeqCst);
+
+        (reward as f64 / REWARD_SCALE as f64, plays)
+    }
+
     pub fn action(&self) -> Option<T::Action> {
         self.action
     }
@@ -302,6 +430,16 @@ where
         *self.end_state_result.read()
     }
 
+    /// The game result this node leads to under perfect play,
+    /// or None if it has not (yet) been proven.
+    pub fn proven_result(&self) -> Option<GameResult> {
+        decode_proven(self.proven_result.load(Ordering::SeqCst))
+    }
+
+    pub fn is_proven(&self) -> bool {
+        self.proven_result().is_some()
+    }
+
     pub fn worst_case_wins_plays(&self) -> (usize, usize) {
         (
             self.sat_worst_case_ratio.0.load(Ordering::SeqCst),
@@ -356,8 +494,43 @@ where
         self.plays.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Must be called after increment_plays() for the same play,
+    /// so a reader that sees the win also sees the play.
     pub fn increment_wins(&self) {
-        self.wins.fetch_add(1, Ordering::Relaxed);
+        self.wins.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Adds the given reward, which must be in the range [0, 1], to this node's total reward.
+    /// Like increment_wins(), must be called after increment_plays() for the same play.
+    pub fn add_reward(&self, reward: f32) {
+        debug_assert!(
+            (0.0..=1.0).contains(&reward),
+            "Rewards must be normalized to [0, 1], but saw {}",
+            reward
+        );
+
+        let fixed = (reward * REWARD_SCALE as f32).round() as usize;
+        self.reward.fetch_add(fixed, Ordering::SeqCst);
+    }
+
+    /// Adds the given final score margin to this node's total margin.
+    pub fn add_margin(&self, margin: isize) {
+        self.margin.fetch_add(margin, Ordering::Relaxed);
+    }
+
+    /// Counts an All-Moves-As-First play with the given reward, which must be in the range [0, 1].
+    /// As with increment_plays() and add_reward(), the play is counted before the reward.
+    pub fn add_amaf_play(&self, reward: f32) {
+        debug_assert!(
+            (0.0..=1.0).contains(&reward),
+            "Rewards must be normalized to [0, 1], but saw {}",
+            reward
+        );
+
+        let fixed = (reward * REWARD_SCALE as f32).round() as usize;
+
+        self.amaf_plays.fetch_add(1, Ordering::Relaxed);
+        self.amaf_reward.fetch_add(fixed, Ordering::SeqCst);
     }
 
     /// Updates the current worst case wins/plays ratio,
@@ -377,12 +550,122 @@ where
         }
     }
 
+    /// Marks this node as proven to lead to the given result under perfect play.
+    /// A proven result never changes, so this returns false (and does nothing)
+    /// if the node was already proven.
+    pub fn set_proven_result(&self, result: GameResult) -> bool {
+        self.proven_result
+            .compare_exchange(
+                UNPROVEN,
+                encode_proven(result),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_ok()
+    }
+
     pub fn set_end_state_result(&self, result: GameResult) {
         let wl = self.end_state_result.write_lock();
         wl.write(Some(result));
     }
 }
 
+/// A plain snapshot of the statistics of an MctsData, so they can be persisted.
+/// Rewards are kept in their fixed-point form, and game results in their u8 encoding,
+/// so a snapshot restores the statistics exactly.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MctsStats {
+    plays: u64,
+    wins: u64,
+    reward: u64,
+    margin: i64,
+    amaf_plays: u64,
+    amaf_reward: u64,
+    is_expanded: bool,
+    children_count: u64,
+    children_saturated_count: u64,
+    descendants_saturated_count: u64,
+    tree_size: u64,
+    terminal_count: u64,
+    terminal_wins_count: u64,
+    end_state_result: u8,
+    worst_wins: u64,
+    worst_plays: u64,
+    proven_result: u8,
+}
+
+impl MctsStats {
+    pub(crate) fn is_expanded(&self) -> bool {
+        self.is_expanded
+    }
+}
+
+impl<T> MctsData<T>
+where
+    T: GameState,
+{
+    /// Takes a snapshot of this node's statistics.
+    pub(crate) fn stats(&self) -> MctsStats {
+        let load = |a: &AtomicUsize| a.load(Ordering::SeqCst) as u64;
+        let (wins, plays) = self.wins_plays();
+        let (worst_wins, worst_plays) = self.worst_case_wins_plays();
+
+        MctsStats {
+            plays: plays as u64,
+            wins: wins as u64,
+            reward: load(&self.reward),
+            margin: self.margin.load(Ordering::SeqCst) as i64,
+            amaf_plays: load(&self.amaf_plays),
+            amaf_reward: load(&self.amaf_reward),
+            is_expanded: self.is_expanded(),
+            children_count: load(&self.children_count),
+            children_saturated_count: load(&self.children_saturated_count),
+            descendants_saturated_count: load(&self.descendants_saturated_count),
+            tree_size: load(&self.tree_size),
+            terminal_count: load(&self.terminal_count),
+            terminal_wins_count: load(&self.terminal_wins_count),
+            end_state_result: self.end_state_result().map_or(UNPROVEN, encode_proven),
+            worst_wins: worst_wins as u64,
+            worst_plays: worst_plays as u64,
+            proven_result: self.proven_result.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Creates a node with the given state and action, and statistics restored from a snapshot.
+    pub(crate) fn from_stats(state: T, action: Option<T::Action>, stats: &MctsStats) -> Self {
+        let data = Self::new(state, stats.plays as usize, stats.wins as usize, action);
+
+        let store = |a: &AtomicUsize, v: u64| a.store(v as usize, Ordering::SeqCst);
+        store(&data.reward, stats.reward);
+        data.margin.store(stats.margin as isize, Ordering::SeqCst);
+        store(&data.amaf_plays, stats.amaf_plays);
+        store(&data.amaf_reward, stats.amaf_reward);
+        data.is_expanded.store(stats.is_expanded, Ordering::SeqCst);
+        store(&data.children_count, stats.children_count);
+        store(
+            &data.children_saturated_count,
+            stats.children_saturated_count,
+        );
+        store(
+            &data.descendants_saturated_count,
+            stats.descendants_saturated_count,
+        );
+        store(&data.tree_size, stats.tree_size);
+        store(&data.terminal_count, stats.terminal_count);
+        store(&data.terminal_wins_count, stats.terminal_wins_count);
+        store(&data.sat_worst_case_ratio.0, stats.worst_wins);
+        store(&data.sat_worst_case_ratio.1, stats.worst_plays);
+        data.proven_result
+            .store(stats.proven_result, Ordering::SeqCst);
+
+        if let Some(result) = decode_proven(stats.end_state_result) {
+            data.set_end_state_result(result);
+        }
+
+        data
+    }
+}
+
 impl<T> fmt::Display for MctsData<T>
 where
     T: GameState + fmt::Display,
@@ -477,4 +760,52 @@ mod tests {
             "An expanded node with a child count of 7 and a saturated-child count of 8 is impossible so we should panic."
         );
     }
+
+    #[test]
+    fn mean_reward_expects_average_of_added_rewards() {
+        let data = MctsData::new(TicTacToeState::new(), 0, 0, None);
+
+        for &reward in &[1.0, 0.5, 0.25, 0.25] {
+            data.increment_plays();
+            data.add_reward(reward);
+        }
+
+        assert!((data.mean_reward() - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn new_expects_reward_matches_initial_wins() {
+        let data = MctsData::new(TicTacToeState::new(), 10, 4, None);
+
+        assert!((data.mean_reward() - 0.4).abs() < 0.0001);
+    }
+
+    #[test]
+    fn mean_margin_expects_average_of_added_margins() {
+        let data = MctsData::new(TicTacToeState::new(), 0, 0, None);
+
+        for &margin in &[10, -4, 6] {
+            data.increment_plays();
+            data.add_margin(margin);
+        }
+
+        assert!((data.mean_margin() - 4.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn set_proven_result_expects_first_proof_is_kept() {
+        let data = MctsData::new(TicTacToeState::new(), 0, 0, None);
+
+        assert!(!data.is_proven());
+
+        assert!(data.set_proven_result(GameResult::WhiteWins));
+        assert!(!data.set_proven_result(GameResult::Tie));
+
+        assert_eq!(Some(GameResult::WhiteWins), data.proven_result());
+        assert_eq!(
+            Some(GameResult::WhiteWins),
+            data.clone().proven_result(),
+            "Cloning must preserve the proven result."
+        );
+    }
 }
@@ -0,0 +1,464 @@
+use crate::monte_carlo_data::{MctsData, MctsStats};
+use crate::tree::Node;
+use bincode::Options;
+use lib_boardgame::{PlayerColor, StateEncoding};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Bumped whenever the layout of a saved tree changes,
+/// so older files are rejected instead of misread.
+const FORMAT_VERSION: u32 = 2;
+
+/// The most bytes load_tree() will read, so a corrupt length
+/// can't make it read or allocate without bound.
+const MAX_TREE_BYTES: u64 = 1 << 30;
+
+/// An error encountered while saving or loading a tree.
+#[derive(Debug)]
+pub enum PersistError {
+    Io(std::io::Error),
+
+    /// The data could not be encoded or decoded as a tree.
+    Format(String),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(e) => write!(f, "I/O error: {}", e),
+            PersistError::Format(msg) => write!(f, "Invalid tree data: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+impl From<std::io::Error> for PersistError {
+    fn from(e: std::io::Error) -> Self {
+        PersistError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for PersistError {
+    fn from(e: bincode::Error) -> Self {
+        PersistError::Format(e.to_string())
+    }
+}
+
+/// A tree loaded from its saved form.
+pub struct LoadedTree<THandle> {
+    /// The root of the loaded tree, which has no parent.
+    pub root: THandle,
+
+    /// The player whose perspective the tree's statistics were recorded from
+    /// (i.e. the player that performed the search).
+    pub perspective: PlayerColor,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTree {
+    version: u32,
+    perspective: u8,
+    root_state: Vec<u8>,
+
+    /// Every node in the tree, in depth-first order starting with the root.
+    /// The nodes are kept flat, rather than nested, so that neither saving,
+    /// decoding nor loading a tree recurses, however deep the tree is.
+    nodes: Vec<SavedNode>,
+}
+
+/// Only the root's state is saved. Every other node is saved as the index of its action
+/// in its parent's legal moves, and its state is recreated by applying that action.
+#[derive(Serialize, Deserialize)]
+struct SavedNode {
+    action_index: u16,
+    stats: MctsStats,
+
+    /// How many of the following nodes (not counting their own descendants) are children of this one.
+    child_count: u32,
+}
+
+fn save_nodes<TNode, TState>(root: &TNode) -> Vec<SavedNode>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+{
+    let mut nodes = Vec::new();
+    let mut stack = vec![(root.get_handle(), 0)];
+
+    while let Some((handle, action_index)) = stack.pop() {
+        let node = handle.borrow();
+        let data = node.data();
+        let legal_moves = data.state().legal_moves();
+        let children = node.children_read();
+
+        nodes.push(SavedNode {
+            action_index,
+            stats: data.stats(),
+            child_count: children.len() as u32,
+        });
+
+        // Pushed in reverse, so the children are popped (and saved) in their original order.
+        for c in children.iter().rev() {
+            let action = c
+                .borrow()
+                .data()
+                .action()
+                .expect("A child must have an action.");
+            let index = legal_moves
+                .iter()
+                .position(|&a| a == action)
+                .expect("A child's action must be a legal move of its parent.");
+
+            stack.push((c.clone(), index as u16));
+        }
+    }
+
+    nodes
+}
+
+/// A node being loaded, whose children are still being read.
+struct LoadFrame<THandle, TAction> {
+    handle: THandle,
+    legal_moves: Vec<TAction>,
+    remaining: usize,
+    children: Vec<THandle>,
+}
+
+impl<THandle, TAction> LoadFrame<THandle, TAction> {
+    fn new(
+        handle: THandle,
+        legal_moves: Vec<TAction>,
+        saved: &SavedNode,
+    ) -> Result<Self, PersistError> {
+        let remaining = saved.child_count as usize;
+
+        // An unexpanded node's children must be left unwritten,
+        // so the node can still be expanded by a later search.
+        if remaining > 0 && !saved.stats.is_expanded() {
+            return Err(PersistError::Format(
+                "an unexpanded node has children".to_owned(),
+            ));
+        }
+
+        if remaining > legal_moves.len() {
+            return Err(PersistError::Format(format!(
+                "a node has {} children, but only {} legal moves",
+                remaining,
+                legal_moves.len()
+            )));
+        }
+
+        Ok(Self {
+            handle,
+            legal_moves,
+            remaining,
+            children: Vec::with_capacity(remaining),
+        })
+    }
+}
+
+/// Recreates the descendants of the given root from the saved nodes that follow it.
+fn load_descendants<TNode, TState>(
+    root: TNode::Handle,
+    saved_root: &SavedNode,
+    descendants: &[SavedNode],
+) -> Result<(), PersistError>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+{
+    let mut saved_nodes = descendants.iter();
+    let legal_moves = root.borrow().data().state().legal_moves().to_vec();
+    let mut stack = vec![LoadFrame::new(root, legal_moves, saved_root)?];
+
+    while let Some(frame) = stack.last_mut() {
+        if frame.remaining == 0 {
+            let frame = stack.pop().expect("The frame was just seen.");
+            let node = frame.handle.borrow();
+
+            if node.data().is_expanded() {
+                node.children_write_lock().write(frame.children);
+            }
+
+            continue;
+        }
+
+        let saved = saved_nodes
+            .next()
+            .ok_or_else(|| PersistError::Format("the tree is missing nodes".to_owned()))?;
+
+        let action = *frame
+            .legal_moves
+            .get(saved.action_index as usize)
+            .ok_or_else(|| {
+                PersistError::Format(format!(
+                    "action index {} is out of range",
+                    saved.action_index
+                ))
+            })?;
+
+        let parent = frame.handle.borrow();
+        let state = parent.data().state().next_state(action);
+        let legal_moves = state.legal_moves().to_vec();
+        let child = parent.new_child(MctsData::from_stats(state, Some(action), &saved.stats));
+
+        frame.remaining -= 1;
+        frame.children.push(child.clone());
+
+        let child_frame = LoadFrame::new(child, legal_moves, saved)?;
+        stack.push(child_frame);
+    }
+
+    if saved_nodes.next().is_some() {
+        return Err(PersistError::Format(
+            "the tree has nodes outside of it".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Writes the tree rooted at the given node in a compact binary form.
+/// The perspective is the player whose search produced the tree's statistics.
+pub fn save_tree<TNode, TState, W>(
+    root: &TNode,
+    perspective: PlayerColor,
+    writer: W,
+) -> Result<(), PersistError>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+    W: Write,
+{
+    let saved = SavedTree {
+        version: FORMAT_VERSION,
+        perspective: perspective.to_byte(),
+        root_state: root.data().state().encode_state(),
+        nodes: save_nodes(root),
+    };
+
+    bincode::serialize_into(writer, &saved)?;
+
+    Ok(())
+}
+
+/// Reads a tree written by save_tree(), recreating it as a new root.
+pub fn load_tree<TNode, TState, R>(reader: R) -> Result<LoadedTree<TNode::Handle>, PersistError>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+    R: Read,
+{
+    let saved = read_saved_tree(reader, MAX_TREE_BYTES)?;
+
+    if saved.version != FORMAT_VERSION {
+        return Err(PersistError::Format(format!(
+            "unsupported format version {}",
+            saved.version
+        )));
+    }
+
+    let perspective = PlayerColor::from_byte(saved.perspective)
+        .ok_or_else(|| PersistError::Format("invalid perspective".to_owned()))?;
+    let root_state = TState::decode_state(&saved.root_state)
+        .ok_or_else(|| PersistError::Format("invalid root state".to_owned()))?;
+
+    let (saved_root, descendants) = saved
+        .nodes
+        .split_first()
+        .ok_or_else(|| PersistError::Format("the tree has no root".to_owned()))?;
+
+    let root = TNode::new_root(MctsData::from_stats(root_state, None, &saved_root.stats));
+    load_descendants::<TNode, TState>(root.clone(), saved_root, descendants)?;
+
+    Ok(LoadedTree { root, perspective })
+}
+
+/// Decodes a tree in the encoding used by bincode::serialize_into(),
+/// failing once more than the given number of bytes have been read.
+fn read_saved_tree<R: Read>(reader: R, limit: u64) -> Result<SavedTree, PersistError> {
+    let saved = bincode::options()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+        .deserialize_from(reader)?;
+
+    Ok(saved)
+}
+
+/// Saves the tree to the file at the given path, replacing the file if it exists.
+pub fn save_tree_to_file<TNode, TState>(
+    root: &TNode,
+    perspective: PlayerColor,
+    path: impl AsRef<Path>,
+) -> Result<(), PersistError>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    save_tree(root, perspective, &mut writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Loads a tree from the file at the given path.
+pub fn load_tree_from_file<TNode, TState>(
+    path: impl AsRef<Path>,
+) -> Result<LoadedTree<TNode::Handle>, PersistError>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: StateEncoding,
+{
+    load_tree::<TNode, TState, _>(BufReader::new(File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_tree::ArcNode;
+    use lib_boardgame::GameState;
+    use lib_tic_tac_toe::tic_tac_toe_gamestate::TicTacToeState;
+
+    type TestNode = ArcNode<MctsData<TicTacToeState>>;
+
+    fn expand(node: &TestNode) {
+        let state = node.data().state();
+        let legal_moves = state.legal_moves();
+
+        node.data().mark_expanded();
+        node.data().set_children_count(legal_moves.len());
+
+        // Store the children in reverse, so the saved order differs from the legal move order.
+        let children = legal_moves
+            .iter()
+            .rev()
+            .map(|&a| node.new_child(MctsData::new(state.next_state(a), 2, 1, Some(a))))
+            .collect();
+
+        node.children_write_lock().write(children);
+    }
+
+    fn make_tree() -> TestNode {
+        let root = ArcNode::new_root(MctsData::new(TicTacToeState::initial_state(), 18, 9, None));
+        expand(&root);
+
+        let first_child = root.children_read()[0].clone();
+        first_child.data().add_amaf_play(0.25);
+        first_child.data().add_margin(-3);
+        expand(&first_child);
+
+        root
+    }
+
+    fn summarize(node: &TestNode) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:?} {:?} {}",
+            node.data().action(),
+            node.data().stats(),
+            node.data().state()
+        )];
+
+        for child in node.children_read().iter() {
+            lines.extend(summarize(child));
+        }
+
+        lines
+    }
+
+    #[test]
+    fn load_tree_expects_round_trips_saved_tree() {
+        let root = make_tree();
+
+        let mut bytes = Vec::new();
+        save_tree(&root, PlayerColor::White, &mut bytes).unwrap();
+
+        let loaded = load_tree::<TestNode, _, _>(bytes.as_slice()).unwrap();
+
+        assert_eq!(PlayerColor::White, loaded.perspective);
+        assert_eq!(summarize(&root), summarize(&loaded.root));
+
+        // Nodes that were never expanded can still be expanded after loading.
+        let unexpanded = loaded.root.children_read()[1].clone();
+        assert!(!unexpanded.data().is_expanded());
+        expand(&unexpanded);
+        assert_eq!(8, unexpanded.children_read().len());
+    }
+
+    #[test]
+    fn load_tree_with_bad_data_expects_error() {
+        let result = load_tree::<TestNode, _, _>(&[1u8, 2, 3][..]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn read_saved_tree_expects_error_past_limit() {
+        let mut bytes = Vec::new();
+        save_tree(&make_tree(), PlayerColor::White, &mut bytes).unwrap();
+        let len = bytes.len() as u64;
+
+        assert!(read_saved_tree(bytes.as_slice(), len).is_ok());
+        assert!(read_saved_tree(bytes.as_slice(), len - 1).is_err());
+
+        // A root state claiming an enormous length, followed by endless data.
+        let header = [&1u32.to_le_bytes()[..], &[0], &u64::MAX.to_le_bytes()].concat();
+        let endless = header.as_slice().chain(std::io::repeat(0));
+
+        assert!(read_saved_tree(endless, 1 << 16).is_err());
+    }
+
+    /// Saves the tree from make_tree(), changes its saved nodes, and loads the result.
+    fn load_modified<F>(modify: F) -> Result<LoadedTree<TestNode>, PersistError>
+    where
+        F: FnOnce(&mut Vec<SavedNode>),
+    {
+        let mut bytes = Vec::new();
+        save_tree(&make_tree(), PlayerColor::White, &mut bytes).unwrap();
+
+        let mut saved = read_saved_tree(bytes.as_slice(), MAX_TREE_BYTES).unwrap();
+        modify(&mut saved.nodes);
+
+        let bytes = bincode::serialize(&saved).unwrap();
+        load_tree::<TestNode, _, _>(bytes.as_slice())
+    }
+
+    #[test]
+    fn load_tree_with_bad_structure_expects_error() {
+        assert!(load_modified(|_| {}).is_ok());
+        assert!(load_modified(|nodes| nodes.clear()).is_err());
+        assert!(load_modified(|nodes| {
+            nodes.pop();
+        })
+        .is_err());
+        assert!(load_modified(|nodes| nodes[0].child_count -= 1).is_err());
+        assert!(load_modified(|nodes| nodes[0].child_count = 10).is_err());
+
+        // The root's last child was never expanded, so it can't have children.
+        assert!(load_modified(|nodes| nodes.last_mut().unwrap().child_count = 1).is_err());
+    }
+
+    #[test]
+    fn load_tree_with_deep_chain_expects_error_without_overflow() {
+        // Far deeper than any tic-tac-toe game, and deep enough
+        // to overflow the stack if saved nodes were nested.
+        let result = load_modified(|nodes| {
+            let stats = nodes[0].stats.clone();
+            let chain = (0..100_000).map(|_| SavedNode {
+                action_index: 0,
+                stats: stats.clone(),
+                child_count: 1,
+            });
+
+            *nodes = chain.collect();
+        });
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::monte_carlo_data::MctsData;
+use crate::tree::Node;
+use lib_boardgame::GameState;
+use std::borrow::Borrow;
+
+/// A single ply of a principal variation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PvStep<TAction> {
+    pub action: TAction,
+    pub wins: usize,
+    pub plays: usize,
+}
+
+impl<TAction> PvStep<TAction> {
+    /// The ratio of wins to plays at this ply, from the perspective
+    /// of the player that performed the search.
+    pub fn win_rate(&self) -> f32 {
+        self.wins as f32 / usize::max(1, self.plays) as f32
+    }
+}
+
+/// Walks the most-visited path from the given node, returning each ply along it.
+/// Ties go to the earlier child, and the walk stops at the first node with no visited children.
+pub fn principal_variation<TNode, TState>(root: &TNode) -> Vec<PvStep<TState::Action>>
+where
+    TNode: Node<Data = MctsData<TState>>,
+    TState: GameState,
+{
+    let mut line = Vec::new();
+    let mut cur_node = root.get_handle();
+
+    loop {
+        let next = {
+            let node: &TNode = cur_node.borrow();
+            let children = node.children_read();
+
+            children
+                .iter()
+                .map(|c| c.borrow())
+                .filter(|c: &&TNode| c.data().wins_plays().1 > 0)
+                .fold(None, |best: Option<&TNode>, c| match best {
+                    Some(b) if b.data().wins_plays().1 >= c.data().wins_plays().1 => Some(b),
+                    _ => Some(c),
+                })
+                .map(|c| c.get_handle())
+        };
+
+        match next {
+            Some(child) => {
+                let data = child.borrow().data();
+                let (wins, plays) = data.wins_plays();
+
+                line.push(PvStep {
+                    action: data.action().expect("A child must have an action."),
+                    wins,
+                    plays,
+                });
+
+                cur_node = child;
+            }
+            None => return line,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc_tree::ArcNode;
+    use lib_tic_tac_toe::tic_tac_toe_gamestate::{TicTacToeAction, TicTacToeState};
+    use std::str::FromStr;
+
+    fn child(
+        parent: &ArcNode<MctsData<TicTacToeState>>,
+        action: &str,
+        plays: usize,
+        wins: usize,
+    ) -> ArcNode<MctsData<TicTacToeState>> {
+        let action = TicTacToeAction::from_str(action).unwrap();
+        let state = parent.data().state().next_state(action);
+
+        parent.new_child(MctsData::new(state, plays, wins, Some(action)))
+    }
+
+    #[test]
+    fn principal_variation_expects_follows_most_played_children() {
+        let root = ArcNode::new_root(MctsData::new(TicTacToeState::initial_state(), 10, 6, None));
+
+        let a = child(&root, "0,0", 3, 1);
+        let b = child(&root, "1,1", 6, 4);
+        let unvisited = child(&root, "2,2", 0, 0);
+        root.children_write_lock()
+            .write(vec![a, b.clone(), unvisited]);
+
+        let b1 = child(&b, "0,1", 2, 2);
+        let b2 = child(&b, "0,2", 2, 1);
+        b.children_write_lock().write(vec![b1, b2]);
+
+        let pv = principal_variation(&root);
+
+        let actions = pv.iter().map(|s| s.action.to_string()).collect::<Vec<_>>();
+        let expected = ["1,1", "0,1"]
+            .iter()
+            .map(|a| TicTacToeAction::from_str(a).unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        // The tie between b1 and b2 goes to the earlier child.
+        assert_eq!(expected, actions);
+        assert_eq!(6, pv[0].plays);
+        assert!((pv[0].win_rate() - 4.0 / 6.0).abs() < 0.001);
+        assert_eq!(1.0, pv[1].win_rate());
+    }
+
+    #[test]
+    fn principal_variation_when_unvisited_expects_empty() {
+        let root = ArcNode::new_root(MctsData::new(TicTacToeState::initial_state(), 0, 0, None));
+
+        assert!(principal_variation(&root).is_empty());
+    }
+}
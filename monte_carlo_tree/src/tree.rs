@@ -12,8 +12,8 @@ pub trait Node: Sized + Sync {
     fn parent(&self) -> Option<Self::Handle>;
     fn get_handle(&self) -> Self::Handle;
 
-    fn children_write_lock(&self) -> WriteOnceWriteGuard<Vec<Self::Handle>>;
-    fn children_read(&self) -> AtomicRef<Vec<Self::Handle>>;
+    fn children_write_lock(&self) -> WriteOnceWriteGuard<'_, Vec<Self::Handle>>;
+    fn children_read(&self) -> AtomicRef<'_, Vec<Self::Handle>>;
 
     fn new_root(state: Self::Data) -> Self::Handle;
     fn new_child(&self, state: Self::Data) -> Self::Handle;
@@ -1,10 +1,15 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering};
 
 pub(crate) fn clone_atomic_usize(atom: &AtomicUsize) -> AtomicUsize {
     let raw = atom.load(Ordering::SeqCst);
     AtomicUsize::new(raw)
 }
 
+pub(crate) fn clone_atomic_isize(atom: &AtomicIsize) -> AtomicIsize {
+    let raw = atom.load(Ordering::SeqCst);
+    AtomicIsize::new(raw)
+}
+
 pub(crate) fn clone_atomic_bool(atom: &AtomicBool) -> AtomicBool {
     let raw = atom.load(Ordering::SeqCst);
     AtomicBool::new(raw)
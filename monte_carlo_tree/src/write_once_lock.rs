@@ -53,7 +53,7 @@ impl<T: Sized> WriteOnceLock<T> {
         }
     }
 
-    pub fn write_lock(&self) -> WriteOnceWriteGuard<T> {
+    pub fn write_lock(&self) -> WriteOnceWriteGuard<'_, T> {
         let write_lock = self
             .data_write
             .lock()
@@ -63,7 +63,7 @@ impl<T: Sized> WriteOnceLock<T> {
 
     /// Reads the data that was previously written into this wrapper's data store.
     /// Panics if the data store was not previously written to.
-    pub fn read(&self) -> AtomicRef<T> {
+    pub fn read(&self) -> AtomicRef<'_, T> {
         let has_written = self.has_written.load(Ordering::SeqCst);
 
         if has_written {
@@ -0,0 +1,199 @@
+use lib_agents::{MctsAgent, MctsConfig, MctsEndCondition};
+use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use lib_reversi::{
+    format_move_sequence, label_positions, parse_move_sequence, standard_patterns,
+    PatternEvaluator, ReversiPlayerAction, ReversiState, TrainingSample, BOARD_SIZE,
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+const USAGE: &str = "Usage:
+    pattern_trainer selfplay <games> <records-file> [rollouts] [random-moves] [seed]
+    pattern_trainer train <records-file> <weights-file> [epochs] [phases] [learning-rate]
+
+selfplay appends MCTS self-play games to the records file, one game per line
+in Othello notation (e.g. \"f5d6c3...\"). Each game opens with the given number
+of random moves (default 6), so the games differ. The random moves are seeded
+from the given seed plus each game's index, so a run can be repeated; without
+a seed, a random one is chosen (and printed).
+
+train fits the standard pattern set to the positions of every finished game
+in the records file, labelled with each game's final disc margin,
+and writes the weights file.";
+
+const DEFAULT_ROLLOUTS: usize = 2000;
+const DEFAULT_RANDOM_MOVES: usize = 6;
+const DEFAULT_EPOCHS: usize = 20;
+const DEFAULT_PHASES: usize = 6;
+const DEFAULT_LEARNING_RATE: f32 = 0.05;
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("selfplay") => self_play(&args),
+        Some("train") => train(&args),
+        _ => Err("Expected a command.".to_owned()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
+
+/// Parses the optional argument at the given index, or returns the default if it's absent.
+fn arg_or<T: std::str::FromStr>(args: &[String], index: usize, default: T) -> Result<T, String> {
+    match args.get(index) {
+        None => Ok(default),
+        Some(s) => s.parse().map_err(|_| format!("Invalid argument: {}", s)),
+    }
+}
+
+fn self_play(args: &[String]) -> Result<(), String> {
+    let games: usize = args
+        .get(1)
+        .ok_or("Missing the number of games.")?
+        .parse()
+        .map_err(|_| "Invalid number of games.")?;
+    let path = args.get(2).ok_or("Missing the records file.")?;
+    let rollouts = arg_or(args, 3, DEFAULT_ROLLOUTS)?;
+    let random_moves = arg_or(args, 4, DEFAULT_RANDOM_MOVES)?;
+    let seed = arg_or(args, 5, rand::random::<u64>())?;
+
+    println!("Seeding the opening moves with {}", seed);
+
+    let mut records = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+
+    for game in 0..games {
+        let moves = play_game(rollouts, random_moves, seed.wrapping_add(game as u64));
+
+        writeln!(records, "{}", format_move_sequence(&moves)).map_err(|e| e.to_string())?;
+        println!(
+            "Game {}/{}: {}",
+            game + 1,
+            games,
+            format_move_sequence(&moves)
+        );
+    }
+
+    Ok(())
+}
+
+/// Plays one game of MCTS against itself, after the given number of random opening moves,
+/// and returns its moves.
+fn play_game(rollouts: usize, random_moves: usize, seed: u64) -> Vec<ReversiPlayerAction> {
+    let config = MctsConfig {
+        end_condition: MctsEndCondition::RolloutCount(rollouts),
+        ..Default::default()
+    };
+
+    let black = MctsAgent::<ReversiState>::with_config(PlayerColor::Black, config);
+    let white = MctsAgent::<ReversiState>::with_config(PlayerColor::White, config);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut state = ReversiState::initial_state();
+    let mut moves = Vec::new();
+
+    while !state.is_game_over() {
+        let player = state.current_player_turn();
+        let legal_moves = state.legal_moves();
+
+        let action = if moves.len() < random_moves {
+            *legal_moves
+                .choose(&mut rng)
+                .expect("A game in progress must have a legal move.")
+        } else {
+            match player {
+                PlayerColor::Black => black.pick_move(&state, legal_moves),
+                PlayerColor::White => white.pick_move(&state, legal_moves),
+            }
+        };
+
+        state.apply_move(action);
+        black.observe_action(player, action, &state);
+        white.observe_action(player, action, &state);
+        moves.push(action);
+    }
+
+    moves
+}
+
+fn train(args: &[String]) -> Result<(), String> {
+    let records_path = args.get(1).ok_or("Missing the records file.")?;
+    let weights_path = args.get(2).ok_or("Missing the weights file.")?;
+    let epochs = arg_or(args, 3, DEFAULT_EPOCHS)?;
+    let phases = arg_or(args, 4, DEFAULT_PHASES)?;
+    let learning_rate = arg_or(args, 5, DEFAULT_LEARNING_RATE)?;
+
+    let records = std::fs::read_to_string(records_path).map_err(|e| e.to_string())?;
+    let mut evaluator = PatternEvaluator::new(BOARD_SIZE, standard_patterns(), phases)
+        .map_err(|e| e.to_string())?;
+    let samples = load_samples(&evaluator, &records)?;
+
+    if samples.is_empty() {
+        return Err("The records file has no finished games.".to_owned());
+    }
+
+    println!(
+        "Training on {} positions; initial mean squared error {:.2}",
+        samples.len(),
+        evaluator.mean_squared_error(&samples)
+    );
+
+    for epoch in 0..epochs {
+        let error = evaluator.train(&samples, 1, learning_rate);
+        println!(
+            "Epoch {}/{}: mean squared error {:.2}",
+            epoch + 1,
+            epochs,
+            error
+        );
+    }
+
+    evaluator.save(weights_path).map_err(|e| e.to_string())?;
+    println!("Saved weights to {}", weights_path);
+
+    Ok(())
+}
+
+/// Labels the positions of every game in the records, skipping blank lines and '#' comments.
+/// Games that are unfinished, or contain illegal moves, are reported and skipped.
+fn load_samples(
+    evaluator: &PatternEvaluator,
+    records: &str,
+) -> Result<Vec<TrainingSample>, String> {
+    let mut samples = Vec::new();
+
+    for (line_number, line) in records.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let moves = parse_move_sequence::<BOARD_SIZE>(line)
+            .map_err(|e| format!("Line {}: {}", line_number + 1, e))?;
+
+        match label_positions(&moves) {
+            Some(positions) => samples.extend(
+                positions
+                    .iter()
+                    .map(|(state, player, label)| evaluator.sample(state, *player, *label)),
+            ),
+            None => eprintln!(
+                "Line {}: skipping a game that is unfinished or has an illegal move.",
+                line_number + 1
+            ),
+        }
+    }
+
+    Ok(samples)
+}
@@ -0,0 +1,179 @@
+use lib_boardgame::{perft, perft_divide, GameState};
+use lib_connect_four::ConnectFourState;
+use lib_reversi::ReversiState;
+use lib_tic_tac_toe::tic_tac_toe_gamestate::TicTacToeState;
+use std::time::Instant;
+
+const USAGE: &str = "Usage:
+    perft_runner run <game> <depth> [divide]
+    perft_runner verify [max-depth]
+
+Games: reversi, connect-four, tic-tac-toe.
+
+run counts the leaf nodes to the given depth from the initial position,
+optionally broken down by the first move, and reports the time taken.
+verify checks each game's counts against the known values, up to max-depth
+(default 9), and exits with an error if any differ.";
+
+const DEFAULT_VERIFY_DEPTH: usize = 9;
+
+/// The published Othello perft counts, where passes count as moves
+/// and finished games count as leaves (the first games end after 9 moves).
+const REVERSI_COUNTS: [u64; 14] = [
+    4,
+    12,
+    56,
+    244,
+    1396,
+    8200,
+    55092,
+    390216,
+    3005288,
+    24571284,
+    212258800,
+    1939886636,
+    18429641748,
+    184042084512,
+];
+
+/// Tic-tac-toe games can end with a win from the fifth move on, and every game
+/// is over after 9 moves, so from depth 8 the count is the number of possible games.
+const TIC_TAC_TOE_COUNTS: [u64; 9] = [9, 72, 504, 3024, 15120, 56160, 154944, 255168, 255168];
+
+/// No Connect Four game can end before the seventh move, so the counts are powers of 7
+/// until then; at depth 7, the 7 games that fill a column in the first 6 moves have one fewer choice.
+const CONNECT_FOUR_COUNTS: [u64; 7] = [7, 49, 343, 2401, 16807, 117649, 823536];
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args),
+        Some("verify") => verify(&args),
+        _ => Err("Expected a command.".to_owned()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let game = args.get(1).ok_or("Missing the game.")?;
+    let depth = args
+        .get(2)
+        .ok_or("Missing the depth.")?
+        .parse()
+        .map_err(|_| "Invalid depth.")?;
+    let divide = match args.get(3).map(String::as_str) {
+        None => false,
+        Some("divide") => true,
+        Some(other) => return Err(format!("Unknown option: {}", other)),
+    };
+
+    match game.as_str() {
+        "reversi" => run_perft(&ReversiState::initial_state(), depth, divide),
+        "connect-four" => run_perft(&ConnectFourState::initial_state(), depth, divide),
+        "tic-tac-toe" => run_perft(&TicTacToeState::initial_state(), depth, divide),
+        other => return Err(format!("Unknown game: {}", other)),
+    }
+
+    Ok(())
+}
+
+fn run_perft<T: GameState>(state: &T, depth: usize, divide: bool) {
+    let start = Instant::now();
+
+    let count = if divide {
+        let counts = perft_divide(state, depth);
+
+        for (action, count) in &counts {
+            println!("{}: {}", action, count);
+        }
+
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        perft(state, depth)
+    };
+
+    println!("perft({}) = {} {}", depth, count, throughput(count, start));
+}
+
+/// The elapsed time since the start, and the rate at which leaves were counted.
+fn throughput(count: u64, start: Instant) -> String {
+    let seconds = start.elapsed().as_secs_f64();
+
+    format!(
+        "in {:.3}s ({:.0} leaves/s)",
+        seconds,
+        count as f64 / seconds.max(1e-9)
+    )
+}
+
+fn verify(args: &[String]) -> Result<(), String> {
+    let max_depth = match args.get(1) {
+        None => DEFAULT_VERIFY_DEPTH,
+        Some(s) => s.parse().map_err(|_| "Invalid depth.")?,
+    };
+
+    let all_match = [
+        verify_game(
+            "reversi",
+            &ReversiState::initial_state(),
+            &REVERSI_COUNTS,
+            max_depth,
+        ),
+        verify_game(
+            "connect-four",
+            &ConnectFourState::initial_state(),
+            &CONNECT_FOUR_COUNTS,
+            max_depth,
+        ),
+        verify_game(
+            "tic-tac-toe",
+            &TicTacToeState::initial_state(),
+            &TIC_TAC_TOE_COUNTS,
+            max_depth,
+        ),
+    ]
+    .iter()
+    .all(|&matched| matched);
+
+    if all_match {
+        println!("All counts match.");
+        Ok(())
+    } else {
+        Err("Some counts don't match the known values.".to_owned())
+    }
+}
+
+/// Checks the game's counts against the expected counts (for depths 1, 2, ...),
+/// up to the max depth. Returns true if they all match.
+fn verify_game<T: GameState>(name: &str, state: &T, expected: &[u64], max_depth: usize) -> bool {
+    let mut all_match = true;
+
+    for (depth, &expected) in (1..=max_depth).zip(expected) {
+        let start = Instant::now();
+        let count = perft(state, depth);
+
+        let verdict = if count == expected {
+            "ok"
+        } else {
+            all_match = false;
+            "MISMATCH"
+        };
+
+        println!(
+            "{} perft({}) = {} (expected {}) {} {}",
+            name,
+            depth,
+            count,
+            expected,
+            verdict,
+            throughput(count, start)
+        );
+    }
+
+    all_match
+}
@@ -1,12 +1,13 @@
 use crate::util::{log, Log, NboardError};
-use lib_agents::MctsAgent;
-use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use lib_agents::{analyze, MctsAgent, MctsConfig, PvStep};
+use lib_boardgame::{GameAgent, GameState, PlayerColor, PositionSetup};
 use lib_reversi::ReversiPlayerAction;
 use lib_reversi::ReversiState;
 use std::error::Error;
 use std::io::{self, Write};
 
 #[derive(Debug)]
+#[allow(dead_code)]
 enum MsgFromGui {
     NBoard(usize),
     SetDepth(usize),
@@ -20,45 +21,87 @@ enum MsgFromGui {
     Analyze,
 }
 
-/// Represents an NBoard action, which is structured like so:
-/// "A8" is the bottom-left position, "H1" is the top-right.
+/// Represents an NBoard action, in standard Othello notation,
+/// where "A8" is the bottom-left position, and "H1" is the top-right.
 struct NBoardAction(String);
 
 impl From<ReversiPlayerAction> for NBoardAction {
     fn from(action: ReversiPlayerAction) -> Self {
-        let (x_pos, y_pos) = match action {
-            ReversiPlayerAction::PassTurn => return NBoardAction(String::new()),
-            ReversiPlayerAction::Move { position } => (position.col(), position.row()),
-        };
+        match action {
+            ReversiPlayerAction::PassTurn => NBoardAction(String::new()),
+            action => NBoardAction(action.to_string()),
+        }
+    }
+}
 
-        let letter_col = match x_pos {
-            0 => 'a',
-            1 => 'b',
-            2 => 'c',
-            3 => 'd',
-            4 => 'e',
-            5 => 'f',
-            6 => 'g',
-            7 => 'h',
-            _ => panic!("x_pos {} not supported right now", x_pos),
-        };
+/// Loads a precomputed opening tree into the agent, if the given environment variable
+/// names a tree file. The tree must be rooted at the given starting position.
+fn load_opening_tree(agent: &MctsAgent<ReversiState>, env_var: &str, start: &ReversiState) {
+    let path = match std::env::var(env_var) {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+
+    match agent.load_tree(&path, start) {
+        Ok(()) => log(Log::Info(format!("Loaded opening tree from {}", path))),
+        Err(e) => log(Log::Error(format!(
+            "Couldn't load opening tree from {}: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Writes the agent's search reports, as JSON lines, to the file named by
+/// the given environment variable (if set).
+fn write_search_reports(agent: &MctsAgent<ReversiState>, env_var: &str) {
+    let path = match std::env::var(env_var) {
+        Ok(path) => path,
+        Err(_) => return,
+    };
 
-        let num_row = 8 - y_pos;
+    match std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        Ok(file) => agent.write_reports_to(file),
+        Err(e) => log(Log::Error(format!(
+            "Couldn't open search report file {}: {}",
+            path, e
+        ))),
+    }
+}
 
-        let nboard_formatted = format!("{}{}", letter_col, num_row);
+/// Creates an agent for the given player, configured from the environment.
+/// Opening trees are rooted at the initial position, so they're only loaded
+/// for games that start there.
+fn new_agent(color: PlayerColor, start: &ReversiState) -> MctsAgent<ReversiState> {
+    let agent = MctsAgent::<ReversiState>::new(color);
+    let is_standard_start =
+        start.position_string() == ReversiState::initial_state().position_string();
+
+    let (tree_var, reports_var) = match color {
+        PlayerColor::Black => ("REVERSI_OPENING_TREE_BLACK", "REVERSI_SEARCH_REPORTS_BLACK"),
+        PlayerColor::White => ("REVERSI_OPENING_TREE_WHITE", "REVERSI_SEARCH_REPORTS_WHITE"),
+    };
 
-        NBoardAction(nboard_formatted)
+    if is_standard_start {
+        load_opening_tree(&agent, tree_var, start);
     }
+    write_search_reports(&agent, reports_var);
+
+    agent
 }
 
 pub fn run_loop() -> Result<(), Box<dyn Error>> {
-    let mut black = MctsAgent::<ReversiState>::new(PlayerColor::Black);
-    let mut white = MctsAgent::<ReversiState>::new(PlayerColor::White);
+    let mut state = ReversiState::initial_state();
+    let mut start_position = state.position_string();
+
+    let mut black = new_agent(PlayerColor::Black, &state);
+    let mut white = new_agent(PlayerColor::White, &state);
     // let mut black = RandomAgent;
     // let white = RandomAgent;
 
-    let mut state = ReversiState::initial_state();
-
     let mut move_count = 0;
 
     loop {
@@ -71,38 +114,80 @@ pub fn run_loop() -> Result<(), Box<dyn Error>> {
         match parsed {
             MsgFromGui::Ping(n) => writeln_to_stdout(format!("pong {}", n))?,
             MsgFromGui::Move(m) => {
-                let reversi_move = nboard_action_to_reversi_action(NBoardAction(m));
-                apply_action_and_observe(&mut state, reversi_move, &mut black, &mut white);
-                move_count += 1;
+                // A bad move from the GUI is reported, but must not end the engine.
+                let applied = nboard_action_to_reversi_action(NBoardAction(m))
+                    .and_then(|a| apply_action_and_observe(&mut state, a, &mut black, &mut white));
+
+                match applied {
+                    Ok(()) => move_count += 1,
+                    Err(e) => log(Log::Error(format!("Rejected move: {}", e))),
+                }
             }
             MsgFromGui::SetGame(ggf) => {
-                let mut history = parse_game_history(&ggf);
+                let parsed = parse_start_position(&ggf)
+                    .and_then(|start| parse_game_history(&ggf).map(|history| (start, history)));
+
+                let (start, mut history) = match parsed {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        log(Log::Error(format!("Rejected game: {}", e)));
+                        continue;
+                    }
+                };
+
+                // Unless this game continues the one we're following, start over from its start position.
+                if start.position_string() != start_position || history.len() < move_count {
+                    log(Log::Info(format!(
+                        "Starting a new game from: {}",
+                        start.position_string()
+                    )));
+
+                    start_position = start.position_string();
+                    black = new_agent(PlayerColor::Black, &start);
+                    white = new_agent(PlayerColor::White, &start);
+                    state = start;
+                    move_count = 0;
+                }
+
                 history.drain(..move_count);
 
                 for m in &history {
                     log(Log::Info(format!("Saw move: {}", m)));
-                    apply_action_and_observe(&mut state, *m, &mut black, &mut white);
+
+                    if let Err(e) = apply_action_and_observe(&mut state, *m, &mut black, &mut white)
+                    {
+                        log(Log::Error(format!("Rejected move: {}", e)));
+                        break;
+                    }
+
+                    move_count += 1;
+
                     log(Log::Info(format!(
                         "Next state:\n{}",
                         state.human_friendly()
                     )));
                 }
-
-                move_count += history.len();
             }
             MsgFromGui::Go => {
                 log(Log::Info("Running agent to select move...".to_owned()));
 
                 let cur_player = state.current_player_turn();
-                let selected_move = match cur_player {
-                    PlayerColor::Black => {
-                        black.pick_move(&state, state.legal_moves(PlayerColor::Black))
-                    }
-                    PlayerColor::White => {
-                        white.pick_move(&state, state.legal_moves(PlayerColor::White))
-                    }
+                let agent = match cur_player {
+                    PlayerColor::Black => &black,
+                    PlayerColor::White => &white,
                 };
 
+                let selected_move = agent.pick_move(&state, state.legal_moves());
+
+                let pv = agent.principal_variation();
+                if let Some(first) = pv.first() {
+                    writeln_to_stdout(format!(
+                        "status PV: {} ({:.1}%)",
+                        nboard_pv(&pv),
+                        first.win_rate() * 100.0
+                    ))?;
+                }
+
                 let nboard_action: NBoardAction = selected_move.into();
 
                 let agent_name = match cur_player {
@@ -117,21 +202,58 @@ pub fn run_loop() -> Result<(), Box<dyn Error>> {
 
                 writeln_to_stdout(format!("=== {}", nboard_action.0))?;
             }
+            MsgFromGui::Hint(n) => {
+                log(Log::Info(format!("Analyzing the top {} moves...", n)));
+                writeln_to_stdout("status Analyzing")?;
+
+                if !state.is_game_over() {
+                    let budget = MctsConfig::default().end_condition;
+
+                    for analysis in analyze(&state, budget).iter().take(n) {
+                        // NBoard expects: search <pv> <eval> 0 <depth> [<freeform>]
+                        writeln_to_stdout(format!(
+                            "search {} {:.2} 0 {} {:.1}%",
+                            nboard_pv(&analysis.principal_variation),
+                            analysis.mean_margin,
+                            analysis.principal_variation.len(),
+                            analysis.win_rate * 100.0
+                        ))?;
+                    }
+                }
+
+                writeln_to_stdout("status")?;
+            }
             _ => {}
         }
     }
 }
 
+/// Formats a principal variation the way NBoard expects,
+/// as a run of moves with no separators (e.g. "F5D6C3"), with "PA" for a pass.
+fn nboard_pv(pv: &[PvStep<ReversiPlayerAction>]) -> String {
+    pv.iter()
+        .map(|step| match step.action {
+            ReversiPlayerAction::PassTurn => "PA".to_owned(),
+            action => action.to_string().to_uppercase(),
+        })
+        .collect()
+}
+
 fn apply_action_and_observe(
     state: &mut ReversiState,
     action: ReversiPlayerAction,
     black: &mut impl GameAgent<ReversiState>,
     white: &mut impl GameAgent<ReversiState>,
-) {
+) -> Result<(), NboardError> {
     let player_turn = state.current_player_turn();
-    state.apply_move(action);
-    black.observe_action(player_turn, action, &state);
-    white.observe_action(player_turn, action, &state);
+    state
+        .try_apply_move(action)
+        .or_else(|e| NboardError::err(e.to_string()))?;
+
+    black.observe_action(player_turn, action, state);
+    white.observe_action(player_turn, action, state);
+
+    Ok(())
 }
 
 fn parse_msg(msg: &str) -> Result<MsgFromGui, NboardError> {
@@ -143,7 +265,10 @@ fn parse_msg(msg: &str) -> Result<MsgFromGui, NboardError> {
         }
         ["set", "contempt"] => MsgFromGui::SetContempt(0),
         ["move", m] => MsgFromGui::Move(m.to_string()),
-        ["hint"] => MsgFromGui::Hint(0),
+        ["hint", n_str] => match n_str.parse::<usize>() {
+            Ok(n) => MsgFromGui::Hint(n),
+            Err(e) => return NboardError::err(format!("Invalid hint count {:?}: {}", n_str, e)),
+        },
         ["go"] => MsgFromGui::Go,
         ["ping", ping_str] => MsgFromGui::Ping(ping_str.parse::<usize>().unwrap()),
         ["learn"] => MsgFromGui::Learn,
@@ -156,7 +281,31 @@ fn parse_msg(msg: &str) -> Result<MsgFromGui, NboardError> {
     Ok(parsed)
 }
 
-fn parse_game_history(ggf: &str) -> Vec<ReversiPlayerAction> {
+/// Reads the starting position from a GGF game's BO[...] tag, e.g. "BO[8 ---...--- *]",
+/// where the leading 8 is the size of the board. Games without the tag start from the initial position.
+fn parse_start_position(ggf: &str) -> Result<ReversiState, NboardError> {
+    let tag_start = match ggf.find("BO[") {
+        Some(idx) => idx + 3,
+        None => return Ok(ReversiState::initial_state()),
+    };
+
+    let tag = match ggf[tag_start..].find(']') {
+        Some(len) => &ggf[tag_start..tag_start + len],
+        None => return NboardError::err(format!("Unterminated BO tag in: {}", ggf)),
+    };
+
+    let position = match tag.trim().split_once(char::is_whitespace) {
+        Some(("8", position)) => position,
+        Some((size, _)) if size.parse::<usize>().is_ok() => {
+            return NboardError::err(format!("Unsupported board size: {}", size))
+        }
+        _ => tag,
+    };
+
+    ReversiState::parse_position(position).or_else(|e| NboardError::err(e.to_string()))
+}
+
+fn parse_game_history(ggf: &str) -> Result<Vec<ReversiPlayerAction>, NboardError> {
     // (;GM[Othello]PC[NBoard]DT[2019-09-29 03:22:14 GMT]PB[Andy]PW[rustrs]RE[?]TI[5:00]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[C4//5.558]W[C3]B[F5//26.906];)
 
     let mut result = Vec::new();
@@ -167,10 +316,9 @@ fn parse_game_history(ggf: &str) -> Vec<ReversiPlayerAction> {
             let next_b_move = s.find("]B[");
             let next_w_move = s.find("]W[");
 
-            if next_b_move.is_some() && next_w_move.is_some() {
-                Some(usize::min(next_b_move.unwrap(), next_w_move.unwrap()))
-            } else {
-                next_b_move.or(next_w_move)
+            match (next_b_move, next_w_move) {
+                (Some(b), Some(w)) => Some(usize::min(b, w)),
+                _ => next_b_move.or(next_w_move),
             }
         };
 
@@ -180,50 +328,28 @@ fn parse_game_history(ggf: &str) -> Vec<ReversiPlayerAction> {
 
                 // ']B[' or ']W['
                 let color_str: String = s.drain(..3).collect();
-                let _player_color = match color_str.chars().nth(1).expect("must match ]B[ pattern")
-                {
-                    'B' => PlayerColor::Black,
-                    'W' => PlayerColor::White,
-                    c => panic!("Expected 'B' or 'W', saw: {}", c),
+                let _player_color = match color_str.chars().nth(1) {
+                    Some('B') => PlayerColor::Black,
+                    Some('W') => PlayerColor::White,
+                    c => return NboardError::err(format!("Expected 'B' or 'W', saw: {:?}", c)),
                 };
 
                 // C4, F5, etc
-                let ggf_move: String = s.drain(..2).collect();
+                let ggf_move: String = s.chars().take(2).collect();
+                s.drain(..ggf_move.len());
                 let ggf_move = NBoardAction(ggf_move);
-                let reversi_action = nboard_action_to_reversi_action(ggf_move);
+                let reversi_action = nboard_action_to_reversi_action(ggf_move)?;
                 result.push(reversi_action);
             }
-            None => return result,
+            None => return Ok(result),
         }
     }
 }
 
-fn nboard_action_to_reversi_action(n: NBoardAction) -> ReversiPlayerAction {
-    let letter = n.0.chars().nth(0).expect("move_str first letter");
-    let x_pos_val = match letter {
-        'A' => 0,
-        'B' => 1,
-        'C' => 2,
-        'D' => 3,
-        'E' => 4,
-        'F' => 5,
-        'G' => 6,
-        'H' => 7,
-        _ => panic!("Didn't recognize board letter: {}", letter),
-    };
-
-    let y_pos_val =
-        n.0.chars()
-            .nth(1)
-            .expect("move_str second char")
-            .to_string()
-            .parse::<usize>()
-            .expect("move str from nboard had no y val");
-    let y_pos_val = 7 - (y_pos_val - 1);
-
-    let position = lib_reversi::BoardPosition::new(x_pos_val, y_pos_val);
-
-    lib_reversi::ReversiPlayerAction::Move { position }
+/// Converts an NBoard move (e.g. "F5", or "PA" for a pass) into a Reversi action.
+fn nboard_action_to_reversi_action(n: NBoardAction) -> Result<ReversiPlayerAction, NboardError> {
+    n.0.parse::<ReversiPlayerAction>()
+        .or_else(|e| NboardError::err(e.to_string()))
 }
 
 fn read_from_stdin() -> Result<String, Box<dyn Error>> {
@@ -251,7 +377,11 @@ mod tests {
     fn parse_game_history_finds_one_move() {
         let ggf_string = r"(;GM[Othello]PC[NBoard]DT[2019-09-25 06:42:54 GMT]PB[Andy]PW[]RE[?]TI[5:00]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[D3//2.991];)";
 
-        let parsed_move = parse_game_history(ggf_string).into_iter().last().unwrap();
+        let parsed_move = parse_game_history(ggf_string)
+            .unwrap()
+            .into_iter()
+            .last()
+            .unwrap();
 
         match parsed_move {
             ReversiPlayerAction::Move { position } => {
@@ -265,7 +395,7 @@ mod tests {
     fn parse_game_history_finds_all_moves() {
         let ggf_string = r"(;GM[Othello]PC[NBoard]DT[2019-09-29 03:22:14 GMT]PB[Andy]PW[rustrs]RE[?]TI[5:00]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[C4//5.558]W[C3]B[F5//26.906];)";
 
-        let history = parse_game_history(ggf_string);
+        let history = parse_game_history(ggf_string).unwrap();
 
         match history[0] {
             ReversiPlayerAction::Move { position } => {
@@ -291,6 +421,27 @@ mod tests {
         assert_eq!(3, history.len());
     }
 
+    #[test]
+    fn parse_start_position_reads_bo_tag() {
+        let ggf_string = r"(;GM[Othello]PC[NBoard]DT[2019-09-29 03:22:14 GMT]PB[Andy]PW[rustrs]RE[?]TI[5:00]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[C4//5.558]W[C3]B[F5//26.906];)";
+
+        let start = parse_start_position(ggf_string).unwrap();
+
+        assert_eq!(
+            ReversiState::initial_state().position_string(),
+            start.position_string()
+        );
+    }
+
+    #[test]
+    fn parse_start_position_expects_errors_on_bad_input() {
+        let impossible = r"(;GM[Othello]BO[8 ---------------------------------------------------------------- *];)";
+        let wrong_size = r"(;GM[Othello]BO[10 ---------------------------O*------*O--------------------------- *];)";
+
+        assert!(parse_start_position(impossible).is_err());
+        assert!(parse_start_position(wrong_size).is_err());
+    }
+
     #[test]
     fn nboard_action_from_reversi_action() {
         let bottom_left_position = ReversiPlayerAction::Move {
@@ -314,4 +465,51 @@ mod tests {
         let nboard_one_one: NBoardAction = one_one.into();
         assert_eq!(nboard_one_one.0, "b7".to_owned());
     }
+
+    #[test]
+    fn nboard_pv_expects_concatenated_moves() {
+        let step = |action| PvStep {
+            action,
+            wins: 1,
+            plays: 2,
+        };
+
+        let pv = [
+            step(ReversiPlayerAction::Move {
+                position: BoardPosition::new(5, 3),
+            }),
+            step(ReversiPlayerAction::PassTurn),
+            step(ReversiPlayerAction::Move {
+                position: BoardPosition::new(0, 7),
+            }),
+        ];
+
+        assert_eq!("F5PAA1", nboard_pv(&pv));
+    }
+
+    #[test]
+    fn nboard_action_to_reversi_action_expects_errors_on_bad_input() {
+        let parse = |s: &str| nboard_action_to_reversi_action(NBoardAction(s.to_owned()));
+
+        assert_eq!(
+            ReversiPlayerAction::Move {
+                position: BoardPosition::new(5, 3)
+            },
+            parse("f5").unwrap()
+        );
+        assert_eq!(ReversiPlayerAction::PassTurn, parse("PA").unwrap());
+
+        for bad in &["", "Z5", "F9", "F0", "F", "F55"] {
+            assert!(parse(bad).is_err(), "Expected an error for {:?}", bad);
+        }
+    }
+
+    #[test]
+    fn parse_msg_hint_expects_errors_on_bad_count() {
+        assert!(matches!(parse_msg("hint 3"), Ok(MsgFromGui::Hint(3))));
+
+        for bad in &["hint", "hint x", "hint -1"] {
+            assert!(parse_msg(bad).is_err(), "Expected an error for {:?}", bad);
+        }
+    }
 }
@@ -3,6 +3,7 @@ use std::fmt::{Debug, Display};
 use std::fs::OpenOptions;
 use std::io::Write;
 
+#[allow(dead_code)]
 pub(super) enum Log {
     Info(String),
     Warning(String),
@@ -34,7 +35,6 @@ pub(super) fn log(log: Log) {
     let log_file_loc = r"C:\Users\Andy\git_repos\reversi_rs\nboard_log.txt";
 
     let mut f = OpenOptions::new()
-        .write(true)
         .create(true)
         .append(true)
         .open(log_file_loc)
@@ -0,0 +1,260 @@
+use lib_agents::ExportedNode;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// The order in which a node's children are listed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SortKey {
+    Plays,
+    WinRate,
+    Reward,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "plays" | "p" => Ok(SortKey::Plays),
+            "winrate" | "w" => Ok(SortKey::WinRate),
+            "reward" | "r" => Ok(SortKey::Reward),
+            other => Err(format!(
+                "Unknown sort key: {} -- expected plays, winrate or reward",
+                other
+            )),
+        }
+    }
+}
+
+fn win_rate(node: &ExportedNode) -> f32 {
+    node.wins as f32 / usize::max(1, node.plays) as f32
+}
+
+fn compare(a: &ExportedNode, b: &ExportedNode, key: SortKey) -> Ordering {
+    let ordering = match key {
+        SortKey::Plays => a.plays.cmp(&b.plays),
+        SortKey::WinRate => win_rate(a).total_cmp(&win_rate(b)),
+        SortKey::Reward => a.mean_reward.total_cmp(&b.mean_reward),
+    };
+
+    // Descending, so the most interesting children come first.
+    ordering.reverse()
+}
+
+/// Tracks the position of the user within an exported search tree.
+pub struct Explorer {
+    root: ExportedNode,
+
+    /// The index of each child taken from the root to reach the current node.
+    path: Vec<usize>,
+
+    sort_key: SortKey,
+}
+
+impl Explorer {
+    pub fn new(root: ExportedNode) -> Self {
+        let mut explorer = Self {
+            root,
+            path: Vec::new(),
+            sort_key: SortKey::Plays,
+        };
+
+        explorer.sort_current();
+
+        explorer
+    }
+
+    pub fn current(&self) -> &ExportedNode {
+        self.path
+            .iter()
+            .fold(&self.root, |node, &i| &node.children[i])
+    }
+
+    fn current_mut(&mut self) -> &mut ExportedNode {
+        let mut node = &mut self.root;
+
+        for &i in &self.path {
+            node = &mut node.children[i];
+        }
+
+        node
+    }
+
+    /// The actions taken from the root to reach the current node.
+    pub fn line(&self) -> Vec<&str> {
+        let mut node = &self.root;
+        let mut line = Vec::new();
+
+        for &i in &self.path {
+            node = &node.children[i];
+            line.push(node.action.as_deref().unwrap_or("?"));
+        }
+
+        line
+    }
+
+    /// Moves to the child at the given index (as listed), if it exists.
+    pub fn enter(&mut self, index: usize) -> bool {
+        if index >= self.current().children.len() {
+            return false;
+        }
+
+        self.path.push(index);
+        self.sort_current();
+
+        true
+    }
+
+    /// Moves to the parent of the current node, if there is one.
+    pub fn up(&mut self) -> bool {
+        let moved = self.path.pop().is_some();
+        self.sort_current();
+
+        moved
+    }
+
+    pub fn go_to_root(&mut self) {
+        self.path.clear();
+        self.sort_current();
+    }
+
+    /// Changes the order children are listed in.
+    pub fn sort_by(&mut self, key: SortKey) {
+        self.sort_key = key;
+        self.sort_current();
+    }
+
+    fn sort_current(&mut self) {
+        let key = self.sort_key;
+
+        self.current_mut()
+            .children
+            .sort_by(|a, b| compare(a, b, key));
+    }
+}
+
+/// A single line summarizing the statistics of a node.
+pub fn summarize(node: &ExportedNode) -> String {
+    let sat = if node.is_saturated { " (S)" } else { "" };
+    let proven = match &node.proven {
+        Some(p) => format!(" (Proven: {})", p),
+        None => String::new(),
+    };
+
+    format!(
+        "{:<10} P: {:>8} W: {:>8} ({:.3}) R: {:.3} M: {:>+7.2} TS: {:>8} Term: {}/{} WW/WP: {}/{}{}{}",
+        node.action.as_deref().unwrap_or("root"),
+        node.plays,
+        node.wins,
+        win_rate(node),
+        node.mean_reward,
+        node.mean_margin,
+        node.tree_size,
+        node.terminal_wins_count,
+        node.terminal_count,
+        node.worst_wins,
+        node.worst_plays,
+        sat,
+        proven
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(action: &str, wins: usize, plays: usize, children: Vec<ExportedNode>) -> ExportedNode {
+        ExportedNode {
+            action: Some(action.to_owned()),
+            wins,
+            plays,
+            mean_reward: wins as f32 / plays as f32,
+            mean_margin: 0.0,
+            tree_size: children.len(),
+            is_saturated: false,
+            worst_wins: 0,
+            worst_plays: 0,
+            terminal_count: 0,
+            terminal_wins_count: 0,
+            proven: None,
+            state: None,
+            children,
+        }
+    }
+
+    fn make_tree() -> ExportedNode {
+        let a = node(
+            "a",
+            1,
+            10,
+            vec![node("a1", 1, 4, vec![]), node("a2", 0, 5, vec![])],
+        );
+        let b = node("b", 18, 20, vec![]);
+        let c = node("c", 4, 5, vec![]);
+
+        node("root", 23, 35, vec![a, b, c])
+    }
+
+    #[test]
+    fn new_expects_children_sorted_by_plays() {
+        let explorer = Explorer::new(make_tree());
+
+        let actions = explorer
+            .current()
+            .children
+            .iter()
+            .map(|c| c.action.clone().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["b", "a", "c"], actions);
+    }
+
+    #[test]
+    fn sort_by_win_rate_expects_best_ratio_first() {
+        let mut explorer = Explorer::new(make_tree());
+        explorer.sort_by(SortKey::WinRate);
+
+        assert_eq!(Some("b"), explorer.current().children[0].action.as_deref());
+        assert_eq!(Some("a"), explorer.current().children[2].action.as_deref());
+    }
+
+    #[test]
+    fn sort_by_reward_with_nan_expects_no_panic() {
+        let mut tree = make_tree();
+        tree.children[0].mean_reward = f32::NAN;
+
+        let mut explorer = Explorer::new(tree);
+        explorer.sort_by(SortKey::Reward);
+
+        let actions = explorer
+            .current()
+            .children
+            .iter()
+            .filter_map(|c| c.action.as_deref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["a", "b", "c"], actions);
+    }
+
+    #[test]
+    fn enter_and_up_expects_navigates_tree() {
+        let mut explorer = Explorer::new(make_tree());
+
+        assert!(!explorer.up());
+        assert!(!explorer.enter(3));
+
+        // "a" is listed second when sorted by plays.
+        assert!(explorer.enter(1));
+        assert_eq!(vec!["a"], explorer.line());
+
+        // Entering a node sorts its children too.
+        assert!(explorer.enter(0));
+        assert_eq!(vec!["a", "a2"], explorer.line());
+
+        assert!(explorer.up());
+        assert_eq!(vec!["a"], explorer.line());
+
+        explorer.go_to_root();
+        assert!(explorer.line().is_empty());
+    }
+}
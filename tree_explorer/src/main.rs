@@ -0,0 +1,213 @@
+mod explorer;
+
+use explorer::{summarize, Explorer, SortKey};
+use lib_agents::{analyze, ExportOptions, ExportedNode, MctsAgent, MctsConfig, MctsEndCondition};
+use lib_boardgame::{GameAgent, GameState, PlayerColor};
+use lib_reversi::ReversiState;
+use std::io::{stdin, BufRead};
+
+const USAGE: &str = "Usage:
+    tree_explorer load <tree.json>
+    tree_explorer search <board-file> [black|white] [rollouts]
+    tree_explorer analyze <board-file> [black|white] [rollouts]
+
+A board file holds a board as printed by the game, e.g. \"- - X O ...\",
+or - to read the board from stdin.";
+
+const HELP: &str = "Commands:
+    <n>                 enter child n
+    u                   go up to the parent
+    r                   go to the root
+    s plays|winrate|reward
+                        sort children
+    b                   show the board at this node
+    h                   show this help
+    q                   quit";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if args.first().map(String::as_str) == Some("analyze") {
+        if let Err(e) = run_analysis(&args) {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let tree = match load_tree(&args) {
+        Ok(tree) => tree,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    explore(Explorer::new(tree));
+}
+
+fn load_tree(args: &[String]) -> Result<ExportedNode, String> {
+    match args.first().map(String::as_str) {
+        Some("load") => {
+            let path = args.get(1).ok_or("Missing the tree file.")?;
+            let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+            serde_json::from_str(&json).map_err(|e| e.to_string())
+        }
+        Some("search") => {
+            let (state, rollouts) = parse_position(args)?;
+
+            search(&state, rollouts)
+        }
+        _ => Err("Expected a command.".to_owned()),
+    }
+}
+
+/// Parses the board file, player to move, and rollout count following a command.
+fn parse_position(args: &[String]) -> Result<(ReversiState, usize), String> {
+    let path = args.get(1).ok_or("Missing the board file.")?;
+    let board_str = if path == "-" {
+        std::io::read_to_string(stdin()).map_err(|e| e.to_string())?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| e.to_string())?
+    };
+
+    let color = match args.get(2).map(String::as_str) {
+        None | Some("black") => PlayerColor::Black,
+        Some("white") => PlayerColor::White,
+        Some(other) => return Err(format!("Unknown color: {}", other)),
+    };
+
+    let rollouts = match args.get(3) {
+        Some(r) => r.parse::<usize>().map_err(|e| e.to_string())?,
+        None => 10_000,
+    };
+
+    let mut state = ReversiState::from_board_str(&board_str);
+
+    if color == PlayerColor::White {
+        state.skip_turn();
+    }
+
+    if state.is_game_over() {
+        return Err("The game is already over on the given board.".to_owned());
+    }
+
+    Ok((state, rollouts))
+}
+
+/// Evaluates every legal move on the given board, and prints one line per move.
+fn run_analysis(args: &[String]) -> Result<(), String> {
+    let (state, rollouts) = parse_position(args)?;
+
+    let analysis = analyze(&state, MctsEndCondition::RolloutCount(rollouts));
+
+    println!(
+        "{:<10} {:>8} {:>7} {:>7} {:>8}  PV",
+        "Move", "Plays", "Share", "Win", "Margin"
+    );
+
+    for analysis in analysis {
+        let proven = match analysis.proven {
+            Some(result) => format!(" (Proven: {:?})", result),
+            None => String::new(),
+        };
+
+        let pv = analysis
+            .principal_variation
+            .iter()
+            .map(|step| step.action.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!(
+            "{:<10} {:>8} {:>6.1}% {:>6.1}% {:>+8.2}  {}{}",
+            analysis.action.to_string(),
+            analysis.plays,
+            analysis.visit_share * 100.0,
+            analysis.win_rate * 100.0,
+            analysis.mean_margin,
+            pv,
+            proven
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs a search on the given Reversi state, for the player to move,
+/// and captures the entire resulting tree.
+fn search(state: &ReversiState, rollouts: usize) -> Result<ExportedNode, String> {
+    let color = state.current_player_turn();
+
+    let config = MctsConfig {
+        end_condition: MctsEndCondition::RolloutCount(rollouts),
+        ..Default::default()
+    };
+
+    let agent = MctsAgent::<ReversiState>::with_config(color, config);
+    agent.pick_move(state, state.legal_moves());
+
+    let options = ExportOptions {
+        max_depth: usize::MAX,
+        min_plays: 1,
+        include_states: true,
+    };
+
+    agent
+        .export_search_tree(options)
+        .ok_or_else(|| "The search produced no tree.".to_owned())
+}
+
+fn print_node(explorer: &Explorer) {
+    let node = explorer.current();
+
+    println!();
+    println!("Line: {}", explorer.line().join(" "));
+    println!("{}", summarize(node));
+
+    for (i, child) in node.children.iter().enumerate() {
+        println!("  [{:>2}] {}", i, summarize(child));
+    }
+}
+
+fn explore(mut explorer: Explorer) {
+    println!("{}", HELP);
+    print_node(&explorer);
+
+    let stdin = stdin();
+
+    for line in stdin.lock().lines() {
+        let line = line.expect("Couldn't capture user input.");
+        let mut words = line.split_whitespace();
+
+        match (words.next(), words.next()) {
+            (Some("q"), _) => return,
+            (Some("h"), _) => println!("{}", HELP),
+            (Some("u"), _) => {
+                if !explorer.up() {
+                    println!("Already at the root.");
+                }
+            }
+            (Some("r"), _) => explorer.go_to_root(),
+            (Some("s"), Some(key)) => match key.parse::<SortKey>() {
+                Ok(key) => explorer.sort_by(key),
+                Err(e) => println!("{}", e),
+            },
+            (Some("b"), _) => match &explorer.current().state {
+                Some(state) => println!("{}", state),
+                None => println!("This tree was exported without states."),
+            },
+            (Some(n), None) if n.parse::<usize>().is_ok() => {
+                if !explorer.enter(n.parse().unwrap()) {
+                    println!("No child at index {}.", n);
+                }
+            }
+            (None, _) => {}
+            _ => println!("Unknown command.\n{}", HELP),
+        }
+
+        print_node(&explorer);
+    }
+}